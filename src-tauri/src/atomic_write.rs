@@ -0,0 +1,210 @@
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::task;
+
+use crate::config_lock::Fingerprint;
+
+// Every client config goes through here. The new contents are written to a
+// temp file next to the target, synced to disk, given the target's permissions
+// and renamed over it, so a crash or a full disk leaves either the old file or
+// the new one, never a truncated mix.
+
+/// Atomically replace `path` with `contents`
+pub fn write_file_blocking(path: &Path, contents: &[u8]) -> Result<(), String> {
+    write(path, contents, None).map(|_| ())
+}
+
+/// Async wrapper around [`write_file_blocking`]
+pub async fn write_file(path: &Path, contents: Vec<u8>) -> Result<(), String> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || write_file_blocking(&path, &contents))
+        .await
+        .map_err(|e| format!("Failed to run blocking task for file write: {}", e))?
+}
+
+/// Like [`write_file_blocking`], but only if the file still matches `expected`
+/// right before the rename. Returns `false`, leaving the file alone, when it
+/// was changed in the meantime.
+pub fn write_file_if_unchanged_blocking(
+    path: &Path,
+    contents: &[u8],
+    expected: &Fingerprint,
+) -> Result<bool, String> {
+    write(path, contents, Some(expected))
+}
+
+/// Async wrapper around [`write_file_if_unchanged_blocking`]
+pub async fn write_file_if_unchanged(
+    path: &Path,
+    contents: Vec<u8>,
+    expected: Fingerprint,
+) -> Result<bool, String> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || write_file_if_unchanged_blocking(&path, &contents, &expected))
+        .await
+        .map_err(|e| format!("Failed to run blocking task for file write: {}", e))?
+}
+
+fn write(path: &Path, contents: &[u8], expected: Option<&Fingerprint>) -> Result<bool, String> {
+    let target = resolve_target(path)?;
+
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let tmp_path = temp_path(&dir, &target);
+    let result = write_temp(&tmp_path, &target, contents).and_then(|_| {
+        if let Some(expected) = expected
+            && !expected.matches_disk(&target)?
+        {
+            return Ok(false);
+        }
+        fs::rename(&tmp_path, &target)
+            .map(|_| true)
+            .map_err(|e| format!("Failed to replace file: {}", e))
+    });
+    if !matches!(result, Ok(true)) {
+        let _ = fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    sync_dir(&dir);
+    Ok(true)
+}
+
+/// Write through symlinks (e.g. dotfiles managed elsewhere) instead of
+/// replacing them; a dangling one gets the file it points to created
+fn resolve_target(path: &Path) -> Result<PathBuf, String> {
+    if !path.is_symlink() {
+        return Ok(path.to_path_buf());
+    }
+    match fs::canonicalize(path) {
+        Ok(target) => Ok(target),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let link = fs::read_link(path).map_err(|e| format!("Failed to read symlink: {}", e))?;
+            Ok(match path.parent() {
+                Some(parent) => parent.join(link),
+                None => link,
+            })
+        }
+        Err(e) => Err(format!("Failed to resolve symlink: {}", e)),
+    }
+}
+
+fn temp_path(dir: &Path, target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "config".to_string());
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    dir.join(format!(".{}.{}.{}.tmp", name, process::id(), nanos))
+}
+
+fn write_temp(tmp_path: &Path, target: &Path, contents: &[u8]) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(tmp_path)
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    file.write_all(contents)
+        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+    file.sync_all()
+        .map_err(|e| format!("Failed to sync temp file: {}", e))?;
+
+    if let Ok(metadata) = fs::metadata(target) {
+        fs::set_permissions(tmp_path, metadata.permissions())
+            .map_err(|e| format!("Failed to copy file permissions: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Persist the rename itself; best effort, and a no-op where directories can't be opened
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::config_lock::read_with_fingerprint_blocking;
+    use std::os::unix::fs::{PermissionsExt, symlink};
+
+    fn files_in(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn keeps_the_original_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        write_file_blocking(&path, b"{\"a\": 1}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\": 1}");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(files_in(dir.path()), ["config.json"]);
+    }
+
+    #[test]
+    fn writes_through_a_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real.json");
+        let link = dir.path().join("link.json");
+        fs::write(&real, "{}").unwrap();
+        symlink(&real, &link).unwrap();
+
+        write_file_blocking(&link, b"new").unwrap();
+        assert!(link.is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "new");
+    }
+
+    #[test]
+    fn creates_the_target_of_a_dangling_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("link.json");
+        symlink("managed/real.json", &link).unwrap();
+
+        write_file_blocking(&link, b"new").unwrap();
+        assert!(link.is_symlink());
+        let real = dir.path().join("managed/real.json");
+        assert_eq!(fs::read_to_string(real).unwrap(), "new");
+    }
+
+    #[test]
+    fn leaves_a_changed_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+        let (_, fingerprint) = read_with_fingerprint_blocking(&path).unwrap();
+        fs::write(&path, "{\"other\": true}").unwrap();
+
+        let written = write_file_if_unchanged_blocking(&path, b"mine", &fingerprint).unwrap();
+        assert!(!written);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"other\": true}");
+        assert_eq!(files_in(dir.path()), ["config.json"]);
+
+        let (_, fingerprint) = read_with_fingerprint_blocking(&path).unwrap();
+        assert!(write_file_if_unchanged_blocking(&path, b"mine", &fingerprint).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "mine");
+    }
+}
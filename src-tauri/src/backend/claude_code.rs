@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value, json};
+use std::path::PathBuf;
+
+use super::transport::supported_transports;
+use super::{Capabilities, ClientBackend, DisabledStyle, ServerSet};
+use crate::claude_code_commands::{self, ClaudeScope, parse_server_config, server_to_json};
+use crate::claude_disabled;
+
+/// Backend for the local scope of Claude Code projects in `~/.claude.json`,
+/// keyed by working directory
+pub struct ClaudeCodeBackend {
+    working_dir: String,
+}
+
+impl ClaudeCodeBackend {
+    pub fn new(working_dir: &str) -> Self {
+        Self {
+            working_dir: working_dir.to_string(),
+        }
+    }
+
+    async fn active_servers(&self) -> Result<Map<String, Value>, String> {
+        let list = claude_code_commands::claude_mcp_list(
+            self.working_dir.clone(),
+            Some(ClaudeScope::Local),
+        )
+        .await?;
+        let mut mapped = Map::new();
+        for s in list {
+            let value = server_to_json(&s)?;
+            mapped.insert(s.name, value);
+        }
+        Ok(mapped)
+    }
+}
+
+#[async_trait]
+impl ClientBackend for ClaudeCodeBackend {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            disabled_style: DisabledStyle::SeparateStore,
+            project_scoped: true,
+            transports: supported_transports("claude_code"),
+            add_overwrites: true,
+            remove_requires_existing: true,
+            reports_outcomes: false,
+        }
+    }
+
+    fn location(&self) -> String {
+        self.working_dir.clone()
+    }
+
+    fn config_files(&self) -> Vec<PathBuf> {
+        [
+            claude_code_commands::get_claude_config_path(None),
+            claude_disabled::get_disabled_path(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn normalize_server(&self, name: &str, config: Value) -> Result<Value, String> {
+        server_to_json(&parse_server_config(name, &config)?)
+    }
+
+    async fn read(&self) -> Result<Value, String> {
+        Ok(json!({"mcpServers": self.active_servers().await?}))
+    }
+
+    async fn read_servers(&self) -> Result<ServerSet, String> {
+        let active = self.active_servers().await?;
+        // A server re-added while parked in the disabled store counts as active
+        let mut disabled = claude_disabled::read_project_disabled(&self.working_dir).await?;
+        disabled.retain(|name, _| !active.contains_key(name));
+        Ok(ServerSet { active, disabled })
+    }
+
+    async fn write_servers(&self, servers: &ServerSet) -> Result<(), String> {
+        println!(
+            "[Backend][ClaudeCode] write servers: active={} disabled={} -> {}",
+            servers.active.len(),
+            servers.disabled.len(),
+            self.working_dir
+        );
+        claude_code_commands::replace_project_servers(&self.working_dir, &servers.active).await?;
+        claude_disabled::replace_project_disabled(&self.working_dir, &servers.disabled).await
+    }
+
+    async fn add(&self, name: String, config: Value) -> Result<Value, String> {
+        println!(
+            "[Backend][ClaudeCode] add server: {} -> {}",
+            name, self.working_dir
+        );
+        let config = self.capabilities().adapt_transport("claude_code", config)?;
+        let server = parse_server_config(&name, &config)?;
+        claude_code_commands::claude_mcp_add(
+            server,
+            self.working_dir.clone(),
+            Some(ClaudeScope::Local),
+        )
+        .await?;
+        self.read().await
+    }
+
+    async fn update(&self, name: String, config: Value) -> Result<Value, String> {
+        println!(
+            "[Backend][ClaudeCode] update server: {} -> {}",
+            name, self.working_dir
+        );
+        let config = self.capabilities().adapt_transport("claude_code", config)?;
+        let server = parse_server_config(&name, &config)?;
+        claude_code_commands::claude_mcp_add(
+            server,
+            self.working_dir.clone(),
+            Some(ClaudeScope::Local),
+        )
+        .await?;
+        self.read().await
+    }
+
+    async fn remove(&self, name: String) -> Result<Value, String> {
+        println!(
+            "[Backend][ClaudeCode] remove server: {} -> {}",
+            name, self.working_dir
+        );
+        claude_code_commands::claude_mcp_remove(
+            name,
+            self.working_dir.clone(),
+            Some(ClaudeScope::Local),
+        )
+        .await?;
+        self.read().await
+    }
+
+    async fn batch_delete(&self, names: Vec<String>) -> Result<Value, String> {
+        println!("[Backend][ClaudeCode] batch delete -> {}", self.working_dir);
+        let mut servers = self.active_servers().await?;
+        servers.retain(|name, _| !names.contains(name));
+        claude_code_commands::replace_project_servers(&self.working_dir, &servers).await?;
+        self.read().await
+    }
+
+    async fn list_disabled(&self) -> Result<Value, String> {
+        claude_disabled::claude_list_disabled(self.working_dir.clone(), Some(ClaudeScope::Local))
+            .await
+    }
+
+    async fn disable(&self, name: String) -> Result<Value, String> {
+        println!(
+            "[Backend][ClaudeCode] disable: {} -> {}",
+            name, self.working_dir
+        );
+        claude_disabled::claude_disable_server(
+            self.working_dir.clone(),
+            name,
+            Some(ClaudeScope::Local),
+        )
+        .await
+    }
+
+    async fn enable(&self, name: String) -> Result<Value, String> {
+        println!(
+            "[Backend][ClaudeCode] enable: {} -> {}",
+            name, self.working_dir
+        );
+        claude_disabled::claude_enable_server(
+            self.working_dir.clone(),
+            name,
+            Some(ClaudeScope::Local),
+        )
+        .await
+    }
+
+    async fn update_disabled(&self, name: String, config: Value) -> Result<Value, String> {
+        println!(
+            "[Backend][ClaudeCode] update disabled: {} -> {}",
+            name, self.working_dir
+        );
+        claude_disabled::claude_update_disabled(
+            self.working_dir.clone(),
+            name,
+            config,
+            Some(ClaudeScope::Local),
+        )
+        .await
+    }
+}
@@ -0,0 +1,273 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use super::Transport;
+use crate::server_meta::{self, INJECTED_KEYS};
+
+// Clients name and shape server fields differently: Windsurf calls the url
+// `serverUrl`, Cline and Roo Code approve tools under different keys, Claude
+// Desktop only knows command/args/env. An entry moving between clients is
+// decoded into a CanonicalServer in the source's dialect and encoded in the
+// target's; fields the target has no place for are dropped and reported.
+
+/// How one client writes server entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// mcp-linker's own config, MCPHub and custom clients: every field, unknown ones kept
+    Generic,
+    ClaudeDesktop,
+    ClaudeCode,
+    Cursor,
+    Windsurf,
+    VsCode,
+    Cline,
+    RooCode,
+    Codex,
+}
+
+/// A field with the same meaning in every client that has it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Field {
+    Command,
+    Args,
+    Env,
+    EnvFile,
+    Cwd,
+    Url,
+    Headers,
+    /// Request timeout in seconds
+    Timeout,
+    /// Tools that run without asking
+    AutoApprove,
+}
+
+impl Field {
+    const ALL: [Field; 9] = [
+        Field::Command,
+        Field::Args,
+        Field::Env,
+        Field::EnvFile,
+        Field::Cwd,
+        Field::Url,
+        Field::Headers,
+        Field::Timeout,
+        Field::AutoApprove,
+    ];
+}
+
+// Disabled state is carried by ServerSet, not by the entry
+const STATE_KEYS: [&str; 3] = ["disabled", "enabled", "isActive"];
+
+impl Dialect {
+    pub fn of(client: &str) -> Dialect {
+        match client {
+            "claude" => Dialect::ClaudeDesktop,
+            "claude_code" => Dialect::ClaudeCode,
+            "cursor" => Dialect::Cursor,
+            "windsurf" => Dialect::Windsurf,
+            "copilot" => Dialect::VsCode,
+            "cline" => Dialect::Cline,
+            "roo_code" => Dialect::RooCode,
+            "codex" => Dialect::Codex,
+            _ => Dialect::Generic,
+        }
+    }
+
+    /// The key a field is stored under, or `None` when the client has no such field
+    pub fn key(self, field: Field) -> Option<&'static str> {
+        use Dialect::*;
+        match (self, field) {
+            (_, Field::Command) => Some("command"),
+            (_, Field::Args) => Some("args"),
+            (_, Field::Env) => Some("env"),
+            (Generic | Cursor | VsCode, Field::EnvFile) => Some("envFile"),
+            (Generic | RooCode | Codex, Field::Cwd) => Some("cwd"),
+            (ClaudeDesktop, Field::Url) => None,
+            (Windsurf, Field::Url) => Some("serverUrl"),
+            (_, Field::Url) => Some("url"),
+            (ClaudeDesktop, Field::Headers) => None,
+            (Codex, Field::Headers) => Some("http_headers"),
+            (_, Field::Headers) => Some("headers"),
+            (Generic | Cline | RooCode, Field::Timeout) => Some("timeout"),
+            (Codex, Field::Timeout) => Some("tool_timeout_sec"),
+            (Generic | Cline, Field::AutoApprove) => Some("autoApprove"),
+            (RooCode, Field::AutoApprove) => Some("alwaysAllow"),
+            _ => None,
+        }
+    }
+
+    /// The `type` written for a transport; `None` for clients that infer it
+    fn type_name(self, transport: Transport) -> Option<&'static str> {
+        use Dialect::*;
+        match (self, transport) {
+            (ClaudeDesktop | Cursor | Windsurf, _) => None,
+            (Cline, Transport::Http) => Some("streamableHttp"),
+            (RooCode, Transport::Http) => Some("streamable-http"),
+            (_, t) => Some(t.as_str()),
+        }
+    }
+}
+
+/// A server entry in a client-independent shape
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanonicalServer {
+    /// The dialect the entry was read in
+    pub source: Dialect,
+    pub transport: Option<Transport>,
+    pub fields: BTreeMap<Field, Value>,
+    /// Keys with no canonical meaning, by their original name
+    pub extra: Map<String, Value>,
+}
+
+impl CanonicalServer {
+    pub fn decode(dialect: Dialect, config: &Value) -> CanonicalServer {
+        let transport = Transport::of(config);
+        let mut server = CanonicalServer {
+            source: dialect,
+            transport,
+            fields: BTreeMap::new(),
+            extra: Map::new(),
+        };
+        let Some(obj) = config.as_object() else {
+            return server;
+        };
+
+        for (key, value) in obj {
+            let field = Field::ALL
+                .into_iter()
+                .find(|f| dialect.key(*f) == Some(key.as_str()));
+            let ignored = STATE_KEYS.contains(&key.as_str())
+                || INJECTED_KEYS.contains(&key.as_str())
+                || (key == "type" && transport.is_some());
+            if let Some(field) = field {
+                server.fields.insert(field, value.clone());
+            } else if ignored {
+                continue;
+            } else {
+                server.extra.insert(key.clone(), value.clone());
+            }
+        }
+        server
+    }
+
+    /// Write the entry in `dialect`; returns it with the keys, as the source
+    /// named them, that the dialect has no place for
+    pub fn encode(&self, dialect: Dialect) -> (Value, Vec<String>) {
+        let mut obj = Map::new();
+        let mut dropped = Vec::new();
+
+        if let Some(name) = self.transport.and_then(|t| dialect.type_name(t)) {
+            obj.insert("type".to_string(), Value::from(name));
+        }
+        for (field, value) in &self.fields {
+            match dialect.key(*field) {
+                Some(key) => {
+                    obj.insert(key.to_string(), value.clone());
+                }
+                None => dropped.extend(self.source.key(*field).map(|k| k.to_string())),
+            }
+        }
+        for (key, value) in &self.extra {
+            if dialect == Dialect::Generic {
+                obj.insert(key.clone(), value.clone());
+            } else {
+                dropped.push(key.clone());
+            }
+        }
+        (Value::Object(obj), dropped)
+    }
+}
+
+/// A server entry rewritten for another client
+#[derive(Debug, Clone, PartialEq)]
+pub struct Translation {
+    pub config: Value,
+    /// Keys of the source entry the target has no place for
+    pub dropped: Vec<String>,
+}
+
+/// Rewrite an entry read from `from_client` for `to_client`. Entries moving
+/// between clients of the same dialect keep their fields, but not the
+/// metadata older versions injected.
+pub fn translate(from_client: &str, to_client: &str, mut config: Value) -> Translation {
+    let (from, to) = (Dialect::of(from_client), Dialect::of(to_client));
+    if from == to || !config.is_object() {
+        server_meta::strip_injected(&mut config);
+        return Translation {
+            config,
+            dropped: Vec::new(),
+        };
+    }
+    let (config, dropped) = CanonicalServer::decode(from, &config).encode(to);
+    Translation { config, dropped }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renames_fields_between_dialects() {
+        let windsurf = json!({"serverUrl": "https://x", "headers": {"A": "1"}});
+        let cursor = translate("windsurf", "cursor", windsurf.clone());
+        assert_eq!(
+            cursor.config,
+            json!({"url": "https://x", "headers": {"A": "1"}})
+        );
+        assert!(cursor.dropped.is_empty());
+
+        let back = translate("cursor", "windsurf", cursor.config);
+        assert_eq!(back.config, windsurf);
+    }
+
+    #[test]
+    fn writes_the_type_each_client_expects() {
+        let cline = json!({"type": "streamableHttp", "url": "https://x", "timeout": 30});
+        let roo = translate("cline", "roo_code", cline);
+        assert_eq!(
+            roo.config,
+            json!({"type": "streamable-http", "url": "https://x", "timeout": 30})
+        );
+
+        let codex = translate("roo_code", "codex", roo.config);
+        assert_eq!(
+            codex.config,
+            json!({"type": "http", "url": "https://x", "tool_timeout_sec": 30})
+        );
+    }
+
+    #[test]
+    fn reports_what_the_target_cannot_hold() {
+        let roo = json!({
+            "command": "npx",
+            "cwd": "/work",
+            "alwaysAllow": ["read"],
+            "disabled": true,
+            "custom": 1
+        });
+        let claude = translate("roo_code", "claude", roo);
+        assert_eq!(claude.config, json!({"command": "npx"}));
+        let mut dropped = claude.dropped;
+        dropped.sort();
+        assert_eq!(dropped, ["alwaysAllow", "custom", "cwd"]);
+    }
+
+    #[test]
+    fn generic_keeps_unknown_keys() {
+        let generic = translate("roo_code", "mcphub", json!({"command": "npx", "custom": 1}));
+        assert_eq!(
+            generic.config,
+            json!({"type": "stdio", "command": "npx", "custom": 1})
+        );
+    }
+
+    #[test]
+    fn same_dialect_only_strips_injected_metadata() {
+        let config =
+            json!({"command": "npx", "custom": 1, "_creator": "mcp-linker", "updated_at": "x"});
+        let translated = translate("cursor", "cursor", config);
+        assert_eq!(translated.config, json!({"command": "npx", "custom": 1}));
+        assert!(translated.dropped.is_empty());
+    }
+}
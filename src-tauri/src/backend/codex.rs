@@ -0,0 +1,295 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::transport::supported_transports;
+use super::{Capabilities, ClientBackend, DisabledStyle, ServerSet};
+use crate::codex as codex_cmds;
+use crate::codex::{McpServerConfig, Outcome, ServerOutcome};
+use crate::config;
+
+fn normalize_codex_config(mut server_config: Value) -> Result<Value, String> {
+    println!("[Codex] normalize input: {}", server_config);
+    // Ensure a "type" discriminator exists for serde(tag="type") enum
+    if server_config.get("type").and_then(|v| v.as_str()).is_none() {
+        if server_config.get("command").is_some() {
+            // stdio style
+            if let Some(obj) = server_config.as_object_mut() {
+                obj.insert("type".into(), Value::from("stdio"));
+            }
+        } else if server_config.get("url").is_some() {
+            // http style
+            if let Some(obj) = server_config.as_object_mut() {
+                obj.insert("type".into(), Value::from("http"));
+            }
+        } else {
+            return Err("missing field `type`".into());
+        }
+    }
+
+    // Other clients call the headers of a remote server `headers`; nulls have
+    // no TOML form
+    if let Some(obj) = server_config.as_object_mut() {
+        if let Some(headers) = obj.remove("headers") {
+            obj.entry("http_headers").or_insert(headers);
+        }
+        obj.retain(|_, v| !v.is_null());
+    }
+
+    // Coerce env values to strings if present under stdio
+    if let Some(env) = server_config.get_mut("env")
+        && let Some(map) = env.as_object_mut()
+    {
+        let keys: Vec<String> = map.keys().cloned().collect();
+        for k in keys {
+            if let Some(v) = map.get(&k) {
+                let s = if v.is_string() {
+                    v.as_str().unwrap().to_string()
+                } else {
+                    v.to_string()
+                };
+                map.insert(k, Value::from(s));
+            }
+        }
+    }
+
+    // Normalize disabled/isActive flags into enabled boolean
+    if let Some(disabled_flag) = server_config.get("disabled").and_then(|v| v.as_bool())
+        && let Some(obj) = server_config.as_object_mut()
+    {
+        if disabled_flag {
+            obj.insert("enabled".into(), Value::from(false));
+        } else {
+            obj.remove("enabled");
+        }
+        obj.remove("disabled");
+    }
+    if let Some(is_active_flag) = server_config.get("isActive").and_then(|v| v.as_bool())
+        && let Some(obj) = server_config.as_object_mut()
+    {
+        if !is_active_flag {
+            obj.insert("enabled".into(), Value::from(false));
+        } else {
+            obj.remove("enabled");
+        }
+        obj.remove("isActive");
+    }
+
+    println!("[Codex] normalize output: {}", server_config);
+    Ok(server_config)
+}
+
+fn parse_codex_config(cfg: Value) -> Result<McpServerConfig, String> {
+    let normalized = normalize_codex_config(cfg)?;
+    serde_json::from_value(normalized)
+        .map_err(|e| format!("Invalid server config for codex: {}", e))
+}
+
+fn servers_to_map(servers: HashMap<String, McpServerConfig>) -> Map<String, Value> {
+    servers
+        .into_iter()
+        .filter_map(|(name, cfg)| {
+            let mut value = serde_json::to_value(cfg).ok()?;
+            if let Some(obj) = value.as_object_mut() {
+                obj.remove("enabled");
+            }
+            Some((name, value))
+        })
+        .collect()
+}
+
+/// Backend for Codex, which keeps its servers in `config.toml` of its home
+/// directory (`$CODEX_HOME`, or `~/.codex`)
+pub struct CodexBackend {
+    config_path: PathBuf,
+}
+
+impl CodexBackend {
+    pub fn new(path: Option<&str>) -> Result<Self, String> {
+        Ok(Self {
+            config_path: config::codex::resolve_config_path(path)?,
+        })
+    }
+
+    async fn servers_response(&self) -> Result<Value, String> {
+        let servers = codex_cmds::read_mcp_servers(&self.config_path).await?;
+        Ok(json!({"mcpServers": servers}))
+    }
+
+    /// The config as `read` returns it, with what the operation did to each
+    /// server under `outcomes`
+    async fn outcome_response(&self, outcomes: Vec<ServerOutcome>) -> Result<Value, String> {
+        for o in outcomes.iter().filter(|o| o.outcome != Outcome::Changed) {
+            println!("[Backend][Codex] {}: {:?}", o.name, o.outcome);
+        }
+        let mut response = self.read().await?;
+        response["outcomes"] = serde_json::to_value(outcomes)
+            .map_err(|e| format!("Failed to serialize outcomes: {}", e))?;
+        Ok(response)
+    }
+
+    async fn disabled_response(&self) -> Result<Value, String> {
+        let disabled = codex_cmds::list_disabled(&self.config_path).await?;
+        Ok(serde_json::to_value(disabled).unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl ClientBackend for CodexBackend {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            disabled_style: DisabledStyle::EnabledFlag,
+            project_scoped: false,
+            transports: supported_transports("codex"),
+            add_overwrites: true,
+            remove_requires_existing: false,
+            reports_outcomes: true,
+        }
+    }
+
+    fn location(&self) -> String {
+        self.config_path.display().to_string()
+    }
+
+    fn config_files(&self) -> Vec<PathBuf> {
+        vec![self.config_path.clone()]
+    }
+
+    fn normalize_server(&self, _name: &str, config: Value) -> Result<Value, String> {
+        let mut value = serde_json::to_value(parse_codex_config(config)?)
+            .map_err(|e| format!("Failed to serialize server config: {}", e))?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.remove("enabled");
+        }
+        Ok(value)
+    }
+
+    async fn read(&self) -> Result<Value, String> {
+        let servers = codex_cmds::read_mcp_servers(&self.config_path).await?;
+        let disabled = codex_cmds::list_disabled(&self.config_path).await?;
+        Ok(json!({ "mcpServers": servers, "__disabled": disabled }))
+    }
+
+    async fn read_servers(&self) -> Result<ServerSet, String> {
+        Ok(ServerSet {
+            active: servers_to_map(codex_cmds::read_mcp_servers(&self.config_path).await?),
+            disabled: servers_to_map(codex_cmds::list_disabled(&self.config_path).await?),
+        })
+    }
+
+    async fn write_servers(&self, servers: &ServerSet) -> Result<(), String> {
+        println!(
+            "[Backend][Codex] write servers: active={}, disabled={}",
+            servers.active.len(),
+            servers.disabled.len()
+        );
+        let mut parsed = HashMap::new();
+        for (name, cfg) in &servers.active {
+            parsed.insert(name.clone(), parse_codex_config(cfg.clone())?);
+        }
+        for (name, cfg) in &servers.disabled {
+            let mut server = parse_codex_config(cfg.clone())?;
+            server.set_enabled(false);
+            parsed.insert(name.clone(), server);
+        }
+        codex_cmds::replace_servers(&self.config_path, parsed).await
+    }
+
+    async fn add(&self, name: String, config: Value) -> Result<Value, String> {
+        println!("[Backend][Codex] add server: {}", name);
+        let config = self.capabilities().adapt_transport("codex", config)?;
+        codex_cmds::add_mcp_server(&self.config_path, name, parse_codex_config(config)?).await?;
+        self.servers_response().await
+    }
+
+    async fn update(&self, name: String, config: Value) -> Result<Value, String> {
+        println!("[Backend][Codex] update server: {}", name);
+        let config = self.capabilities().adapt_transport("codex", config)?;
+        codex_cmds::add_mcp_server(&self.config_path, name, parse_codex_config(config)?).await?;
+        self.servers_response().await
+    }
+
+    async fn remove(&self, name: String) -> Result<Value, String> {
+        println!("[Backend][Codex] remove server: {}", name);
+        let outcome = codex_cmds::delete_mcp_server(&self.config_path, name.clone()).await?;
+        self.outcome_response(vec![ServerOutcome { name, outcome }])
+            .await
+    }
+
+    async fn batch_delete(&self, names: Vec<String>) -> Result<Value, String> {
+        println!("[Backend][Codex] batch delete servers");
+        let outcomes = codex_cmds::batch_delete(&self.config_path, names).await?;
+        self.outcome_response(outcomes).await
+    }
+
+    async fn list_disabled(&self) -> Result<Value, String> {
+        let disabled = codex_cmds::list_disabled(&self.config_path).await?;
+        println!("[Backend][Codex] list disabled: {}", disabled.len());
+        Ok(serde_json::to_value(disabled).unwrap_or_default())
+    }
+
+    async fn disable(&self, name: String) -> Result<Value, String> {
+        println!("[Backend][Codex] disable: {}", name);
+        let outcome = codex_cmds::disable(&self.config_path, &name).await?;
+        self.outcome_response(vec![ServerOutcome { name, outcome }])
+            .await
+    }
+
+    async fn enable(&self, name: String) -> Result<Value, String> {
+        println!("[Backend][Codex] enable: {}", name);
+        let outcome = codex_cmds::enable(&self.config_path, &name).await?;
+        self.outcome_response(vec![ServerOutcome { name, outcome }])
+            .await
+    }
+
+    async fn update_disabled(&self, name: String, config: Value) -> Result<Value, String> {
+        println!("[Backend][Codex] update disabled: {}", name);
+        let parsed = parse_codex_config(config)?;
+        codex_cmds::update_disabled(&self.config_path, &name, parsed).await?;
+        self.disabled_response().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_each_server_instead_of_failing() {
+        crate::test_home::init();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[mcp_servers.a]\ntype = \"stdio\"\ncommand = \"a\"\n\n\
+             [mcp_servers.b]\ntype = \"stdio\"\ncommand = \"b\"\nenabled = false\n",
+        )
+        .unwrap();
+        let backend = CodexBackend::new(path.to_str()).unwrap();
+
+        let response = backend
+            .batch_delete(vec!["a".to_string(), "missing".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(
+            response["outcomes"],
+            json!([
+                {"name": "a", "outcome": "changed"},
+                {"name": "missing", "outcome": "not_found"}
+            ])
+        );
+        assert_eq!(response["mcpServers"], json!({}));
+
+        let response = backend.disable("b".to_string()).await.unwrap();
+        assert_eq!(
+            response["outcomes"],
+            json!([{"name": "b", "outcome": "already_in_state"}])
+        );
+        let response = backend.remove("a".to_string()).await.unwrap();
+        assert_eq!(
+            response["outcomes"],
+            json!([{"name": "a", "outcome": "not_found"}])
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+use super::transport::supported_transports;
+use super::{Capabilities, ClientBackend, ServerSet};
+use crate::client::ClientConfig;
+use crate::json_manager::JsonManager;
+use crate::json_manager::utils::{ClientLayout, client_layout};
+
+/// Backend for clients that keep their servers in a JSON, JSONC, TOML or YAML
+/// document, built in or declared in a descriptor file
+pub struct JsonBackend {
+    client: String,
+    path: PathBuf,
+    layout: ClientLayout,
+}
+
+impl JsonBackend {
+    pub fn new(client: &str, path: Option<&str>) -> Result<Self, String> {
+        let cfg = ClientConfig::new(client, path)?;
+        Ok(Self {
+            client: client.to_string(),
+            path: cfg.get_path().to_path_buf(),
+            layout: client_layout(client),
+        })
+    }
+}
+
+#[async_trait]
+impl ClientBackend for JsonBackend {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            disabled_style: self.layout.disabled_style,
+            project_scoped: false,
+            transports: supported_transports(&self.client),
+            add_overwrites: false,
+            remove_requires_existing: false,
+            reports_outcomes: false,
+        }
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn config_files(&self) -> Vec<PathBuf> {
+        vec![self.path.clone()]
+    }
+
+    async fn read(&self) -> Result<Value, String> {
+        let mut json = JsonManager::read_config_file(&self.path, self.layout.format).await?;
+
+        // Ensure the JSON always has the required structure
+        if !json.is_object() {
+            json = json!({});
+        }
+
+        // Ensure the client's servers key exists, and mirror it to "mcpServers"
+        // for clients like VS Code that use a different key
+        let key = self.layout.servers_key.as_str();
+        if !json.as_object().unwrap().contains_key(key) {
+            json[key] = json!({});
+        }
+        if !json.as_object().unwrap().contains_key("mcpServers") {
+            json["mcpServers"] = json[key].clone();
+        }
+
+        Ok(json)
+    }
+
+    async fn read_servers(&self) -> Result<ServerSet, String> {
+        JsonManager::read_server_set(&self.path, &self.client).await
+    }
+
+    async fn write_servers(&self, servers: &ServerSet) -> Result<(), String> {
+        println!(
+            "[Backend][JSON:{}] write servers: active={}, disabled={} -> {}",
+            self.client,
+            servers.active.len(),
+            servers.disabled.len(),
+            self.path.display()
+        );
+        JsonManager::write_server_set(&self.path, &self.client, servers).await
+    }
+
+    async fn add(&self, name: String, config: Value) -> Result<Value, String> {
+        println!(
+            "[Backend][JSON:{}] add server: {} -> {}",
+            self.client,
+            name,
+            self.path.display()
+        );
+        let config = self.capabilities().adapt_transport(&self.client, config)?;
+        JsonManager::add_mcp_server(&self.path, &self.client, &name, config).await
+    }
+
+    async fn update(&self, name: String, config: Value) -> Result<Value, String> {
+        println!(
+            "[Backend][JSON:{}] update server: {} -> {}",
+            self.client,
+            name,
+            self.path.display()
+        );
+        let config = self.capabilities().adapt_transport(&self.client, config)?;
+        JsonManager::update_mcp_server(&self.path, &self.client, &name, config).await
+    }
+
+    async fn remove(&self, name: String) -> Result<Value, String> {
+        println!(
+            "[Backend][JSON:{}] remove server: {} -> {}",
+            self.client,
+            name,
+            self.path.display()
+        );
+        JsonManager::remove_mcp_server(&self.path, &self.client, &name).await
+    }
+
+    async fn batch_delete(&self, names: Vec<String>) -> Result<Value, String> {
+        println!(
+            "[Backend][JSON:{}] batch delete -> {}",
+            self.client,
+            self.path.display()
+        );
+        JsonManager::batch_delete_mcp_servers(&self.path, &self.client, names).await
+    }
+
+    async fn list_disabled(&self) -> Result<Value, String> {
+        println!(
+            "[Backend][JSON:{}] list disabled -> {}",
+            self.client,
+            self.path.display()
+        );
+        JsonManager::list_disabled_servers(&self.path, &self.client).await
+    }
+
+    async fn disable(&self, name: String) -> Result<Value, String> {
+        println!(
+            "[Backend][JSON:{}] disable: {} -> {}",
+            self.client,
+            name,
+            self.path.display()
+        );
+        JsonManager::disable_mcp_server(&self.path, &self.client, &name).await
+    }
+
+    async fn enable(&self, name: String) -> Result<Value, String> {
+        println!(
+            "[Backend][JSON:{}] enable: {} -> {}",
+            self.client,
+            name,
+            self.path.display()
+        );
+        JsonManager::enable_mcp_server(&self.path, &self.client, &name).await
+    }
+
+    async fn update_disabled(&self, name: String, config: Value) -> Result<Value, String> {
+        println!(
+            "[Backend][JSON:{}] update disabled: {} -> {}",
+            self.client,
+            name,
+            self.path.display()
+        );
+        JsonManager::update_disabled_mcp_server(&self.path, &self.client, &name, config).await
+    }
+}
@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+pub mod claude_code;
+pub mod codec;
+pub mod codex;
+pub mod json;
+pub mod preview;
+pub mod registry;
+pub mod transport;
+
+pub use registry::get_backend;
+pub use transport::Transport;
+
+/// How a client marks a server as disabled in its config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisabledStyle {
+    /// Disabled servers are moved into a top-level `__disabled` section
+    Section,
+    /// Disabled servers stay in place with `"disabled": true`
+    DisabledFlag,
+    /// Disabled servers stay in place with `enabled = false` (Codex)
+    EnabledFlag,
+    /// Disabled servers are parked in a separate file (Claude Code)
+    SeparateStore,
+    /// The client has no way to keep a server configured but disabled
+    Unsupported,
+}
+
+/// What a client backend supports, reported to the UI
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub disabled_style: DisabledStyle,
+    /// The client config lives inside a project and needs a path
+    pub project_scoped: bool,
+    pub transports: Vec<Transport>,
+    /// `add` replaces a server of the same name instead of failing
+    pub add_overwrites: bool,
+    /// `remove` fails for a server that isn't configured
+    pub remove_requires_existing: bool,
+    /// `enable` and `disable` report a server that is missing or already in
+    /// that state, leaving the config alone, instead of failing
+    pub reports_outcomes: bool,
+}
+
+impl Capabilities {
+    /// Fit a server entry to the client's transports, bridging it when the
+    /// client can't run it as it is; see [`transport::adapt`]
+    pub fn adapt_transport(&self, client: &str, config: Value) -> Result<Value, String> {
+        transport::adapt(client, &self.transports, config)
+    }
+
+    /// A disabled server shares its entry with the active ones, so adding,
+    /// updating or removing that name touches it too
+    pub fn disabled_in_place(&self) -> bool {
+        matches!(
+            self.disabled_style,
+            DisabledStyle::DisabledFlag | DisabledStyle::EnabledFlag
+        )
+    }
+}
+
+/// Client-independent view of a config: active and disabled servers by name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServerSet {
+    #[serde(rename = "mcpServers", default)]
+    pub active: Map<String, Value>,
+    #[serde(rename = "__disabled", default)]
+    pub disabled: Map<String, Value>,
+}
+
+impl ServerSet {
+    pub fn contains(&self, name: &str) -> bool {
+        self.active.contains_key(name) || self.disabled.contains_key(name)
+    }
+
+    /// A server's config and whether it is disabled
+    pub fn get(&self, name: &str) -> Option<(&Value, bool)> {
+        self.active
+            .get(name)
+            .map(|c| (c, false))
+            .or_else(|| self.disabled.get(name).map(|c| (c, true)))
+    }
+
+    /// Put a server in the active or disabled section, taking it out of the other
+    pub fn insert(&mut self, name: String, config: Value, disabled: bool) {
+        self.remove(&name);
+        if disabled {
+            self.disabled.insert(name, config);
+        } else {
+            self.active.insert(name, config);
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        let active = self.active.remove(name);
+        let disabled = self.disabled.remove(name);
+        active.or(disabled)
+    }
+
+    /// Names of active and disabled servers
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.active.keys().chain(self.disabled.keys())
+    }
+}
+
+/// One implementation per client shape. Every command goes through this trait,
+/// so adding a client means adding a backend and registering it.
+#[async_trait]
+pub trait ClientBackend: Send + Sync {
+    fn capabilities(&self) -> Capabilities;
+
+    /// Where the servers live: the config file, or the project for Claude Code
+    fn location(&self) -> String;
+
+    /// Files the servers are read from; a change to any of them changes the servers
+    fn config_files(&self) -> Vec<PathBuf>;
+
+    /// Bring a server entry into the shape this client stores it in, as
+    /// reading it back after a write would return it
+    fn normalize_server(&self, _name: &str, config: Value) -> Result<Value, String> {
+        Ok(config)
+    }
+
+    /// Read the config in the shape the UI expects (`mcpServers` always present)
+    async fn read(&self) -> Result<Value, String>;
+
+    /// Read active and disabled servers in the normalized shape used by sync
+    async fn read_servers(&self) -> Result<ServerSet, String>;
+
+    /// Replace the server set, keeping everything else in the config untouched
+    async fn write_servers(&self, servers: &ServerSet) -> Result<(), String>;
+
+    async fn add(&self, name: String, config: Value) -> Result<Value, String>;
+
+    async fn update(&self, name: String, config: Value) -> Result<Value, String>;
+
+    async fn remove(&self, name: String) -> Result<Value, String>;
+
+    async fn batch_delete(&self, names: Vec<String>) -> Result<Value, String>;
+
+    async fn list_disabled(&self) -> Result<Value, String>;
+
+    async fn disable(&self, name: String) -> Result<Value, String>;
+
+    async fn enable(&self, name: String) -> Result<Value, String>;
+
+    async fn update_disabled(&self, name: String, config: Value) -> Result<Value, String>;
+}
@@ -0,0 +1,421 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ClientBackend, DisabledStyle, ServerSet};
+
+/// A mutating command, described so its effect can be computed up front
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ServerChange {
+    Add { name: String, config: Value },
+    Update { name: String, config: Value },
+    Remove { name: String },
+    BatchDelete { names: Vec<String> },
+    Disable { name: String },
+    Enable { name: String },
+    UpdateDisabled { name: String, config: Value },
+    Replace { servers: ServerSet },
+}
+
+/// A server that appears in or disappears from the config
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerEntry {
+    pub name: String,
+    pub config: Value,
+    pub disabled: bool,
+}
+
+/// One field of a server entry that changes; `None` means absent
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    /// Dotted path inside the entry, e.g. `env.API_KEY`
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerModification {
+    pub name: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Difference between two server sets, by server name
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSetDiff {
+    pub added: Vec<ServerEntry>,
+    pub removed: Vec<ServerEntry>,
+    pub modified: Vec<ServerModification>,
+    /// Moved from disabled to active
+    pub enabled: Vec<String>,
+    /// Moved from active to disabled
+    pub disabled: Vec<String>,
+}
+
+impl ServerSetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.enabled.is_empty()
+            && self.disabled.is_empty()
+    }
+}
+
+/// What a command would leave in the config, and how that differs from now
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preview {
+    pub servers: ServerSet,
+    pub diff: ServerSetDiff,
+}
+
+/// Compute the effect of `change` on a client's config without writing it
+pub async fn preview(
+    backend: &dyn ClientBackend,
+    client: &str,
+    change: ServerChange,
+) -> Result<Preview, String> {
+    let before = backend.read_servers().await?;
+    let after = apply(backend, client, &before, change)?;
+    let diff = diff_server_sets(&before, &after);
+    Ok(Preview {
+        servers: after,
+        diff,
+    })
+}
+
+/// Apply `change` to `set` the way the client's backend would
+pub fn apply(
+    backend: &dyn ClientBackend,
+    client: &str,
+    set: &ServerSet,
+    change: ServerChange,
+) -> Result<ServerSet, String> {
+    let capabilities = backend.capabilities();
+    let supports_disabled = capabilities.disabled_style != DisabledStyle::Unsupported;
+    let no_disabled = || format!("Client '{}' does not support disabled servers", client);
+    // Where a disabled server shares the entry, writing the name replaces it
+    // as active and removing the name removes it; Claude Code reads a server
+    // that is both active and parked as active only
+    let in_place = capabilities.disabled_in_place();
+    let hides_disabled = in_place || capabilities.disabled_style == DisabledStyle::SeparateStore;
+    let exists = |set: &ServerSet, name: &str| {
+        set.active.contains_key(name) || (in_place && set.disabled.contains_key(name))
+    };
+    let write_entry = |set: &mut ServerSet, name: String, config: Value| {
+        if hides_disabled {
+            set.insert(name, config, false);
+        } else {
+            set.active.insert(name, config);
+        }
+    };
+    let mut set = set.clone();
+
+    match change {
+        ServerChange::Add { name, config } => {
+            let config = capabilities.adapt_transport(client, config)?;
+            if !capabilities.add_overwrites && exists(&set, &name) {
+                return Err(format!("Server '{}' already exists", name));
+            }
+            let config = backend.normalize_server(&name, config)?;
+            write_entry(&mut set, name, config);
+        }
+        ServerChange::Update { name, config } => {
+            let config = capabilities.adapt_transport(client, config)?;
+            let config = backend.normalize_server(&name, config)?;
+            if capabilities.disabled_style == DisabledStyle::Section
+                && set.disabled.contains_key(&name)
+                && !set.active.contains_key(&name)
+            {
+                set.disabled.insert(name, config);
+            } else {
+                write_entry(&mut set, name, config);
+            }
+        }
+        ServerChange::Remove { name } => {
+            if capabilities.remove_requires_existing && !exists(&set, &name) {
+                return Err(format!("MCP server '{}' not found", name));
+            }
+            set.active.remove(&name);
+            if in_place {
+                set.disabled.remove(&name);
+            }
+        }
+        ServerChange::BatchDelete { names } => {
+            // A separate store of disabled servers is left alone
+            let keep_disabled = capabilities.disabled_style == DisabledStyle::SeparateStore;
+            for name in &names {
+                set.active.remove(name);
+                if !keep_disabled {
+                    set.disabled.remove(name);
+                }
+            }
+        }
+        ServerChange::Disable { name } => {
+            if !supports_disabled {
+                return Err(no_disabled());
+            }
+            if capabilities.reports_outcomes && !set.active.contains_key(&name) {
+                return Ok(set);
+            }
+            let config = set
+                .active
+                .remove(&name)
+                .ok_or_else(|| format!("Server '{}' not found in active servers", name))?;
+            set.disabled.insert(name, config);
+        }
+        ServerChange::Enable { name } => {
+            if !supports_disabled {
+                return Err(no_disabled());
+            }
+            if capabilities.reports_outcomes && !set.disabled.contains_key(&name) {
+                return Ok(set);
+            }
+            if set.active.contains_key(&name) {
+                return Err(format!(
+                    "Server '{}' already exists in active servers",
+                    name
+                ));
+            }
+            let config = set
+                .disabled
+                .remove(&name)
+                .ok_or_else(|| format!("Server '{}' not found in disabled servers", name))?;
+            set.active.insert(name, config);
+        }
+        ServerChange::UpdateDisabled { name, config } => {
+            if !supports_disabled {
+                return Err(no_disabled());
+            }
+            let config = backend.normalize_server(&name, config)?;
+            set.disabled.insert(name, config);
+        }
+        ServerChange::Replace { servers } => {
+            let before = set;
+            set = ServerSet::default();
+            for (name, config) in servers.active {
+                let config = normalize_changed(backend, &name, config, before.active.get(&name))?;
+                set.active.insert(name, config);
+            }
+            // Backends without a disabled representation drop them on write
+            if supports_disabled {
+                for (name, config) in servers.disabled {
+                    let config =
+                        normalize_changed(backend, &name, config, before.disabled.get(&name))?;
+                    set.disabled.insert(name, config);
+                }
+            }
+        }
+    }
+    Ok(set)
+}
+
+/// Entries read back from the client are already in its shape
+fn normalize_changed(
+    backend: &dyn ClientBackend,
+    name: &str,
+    config: Value,
+    current: Option<&Value>,
+) -> Result<Value, String> {
+    if current == Some(&config) {
+        Ok(config)
+    } else {
+        backend.normalize_server(name, config)
+    }
+}
+
+/// Compare two server sets entry by entry
+pub fn diff_server_sets(before: &ServerSet, after: &ServerSet) -> ServerSetDiff {
+    let mut diff = ServerSetDiff::default();
+
+    let lookup = |set: &ServerSet, name: &str| -> Option<(Value, bool)> {
+        set.get(name)
+            .map(|(config, disabled)| (config.clone(), disabled))
+    };
+    let mut names: Vec<&String> = before.names().chain(after.names()).collect();
+    names.sort();
+    names.dedup();
+
+    for name in names {
+        match (lookup(before, name), lookup(after, name)) {
+            (None, Some((config, disabled))) => diff.added.push(ServerEntry {
+                name: name.clone(),
+                config,
+                disabled,
+            }),
+            (Some((config, disabled)), None) => diff.removed.push(ServerEntry {
+                name: name.clone(),
+                config,
+                disabled,
+            }),
+            (Some((old, was_disabled)), Some((new, is_disabled))) => {
+                if was_disabled && !is_disabled {
+                    diff.enabled.push(name.clone());
+                } else if !was_disabled && is_disabled {
+                    diff.disabled.push(name.clone());
+                }
+                let mut changes = Vec::new();
+                diff_fields("", Some(&old), Some(&new), &mut changes);
+                if !changes.is_empty() {
+                    diff.modified.push(ServerModification {
+                        name: name.clone(),
+                        changes,
+                    });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    diff
+}
+
+/// Walk objects key by key; anything else is compared as a whole
+pub fn diff_fields(
+    path: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    out: &mut Vec<FieldChange>,
+) {
+    if old == new {
+        return;
+    }
+    if let (Some(Value::Object(old)), Some(Value::Object(new))) = (old, new) {
+        let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let child = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            diff_fields(&child, old.get(key), new.get(key), out);
+        }
+        return;
+    }
+    out.push(FieldChange {
+        path: path.to_string(),
+        before: old.cloned(),
+        after: new.cloned(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::codex::CodexBackend;
+    use crate::backend::json::JsonBackend;
+    use serde_json::json;
+
+    fn set(active: Value, disabled: Value) -> ServerSet {
+        serde_json::from_value(json!({"mcpServers": active, "__disabled": disabled})).unwrap()
+    }
+
+    fn add(name: &str, command: &str) -> ServerChange {
+        ServerChange::Add {
+            name: name.to_string(),
+            config: json!({"command": command}),
+        }
+    }
+
+    fn remove(name: &str) -> ServerChange {
+        ServerChange::Remove {
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn codex_add_replaces_and_remove_takes_disabled_servers() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = CodexBackend::new(Some(dir.path().to_str().unwrap())).unwrap();
+        let before = set(
+            json!({"a": {"command": "old"}}),
+            json!({"b": {"command": "b"}}),
+        );
+
+        let after = apply(&backend, "codex", &before, add("a", "new")).unwrap();
+        assert_eq!(after.active["a"]["command"], "new");
+
+        // Writing a disabled server's name replaces its entry as active
+        let after = apply(&backend, "codex", &before, add("b", "new")).unwrap();
+        assert_eq!(after.active["b"]["command"], "new");
+        assert!(after.disabled.is_empty());
+
+        let after = apply(&backend, "codex", &before, remove("b")).unwrap();
+        assert!(!after.contains("b"));
+        // Codex reports a missing server as not found instead of failing
+        let after = apply(&backend, "codex", &before, remove("missing")).unwrap();
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn json_add_refuses_existing_and_remove_tolerates_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mcp.json");
+        let backend = JsonBackend::new("cursor", Some(path.to_str().unwrap())).unwrap();
+        let before = set(
+            json!({"a": {"command": "a"}}),
+            json!({"b": {"command": "b"}}),
+        );
+
+        assert!(apply(&backend, "cursor", &before, add("a", "new")).is_err());
+        let after = apply(&backend, "cursor", &before, remove("missing")).unwrap();
+        assert_eq!(after, before);
+
+        // A section keeps disabled servers apart from active ones
+        let after = apply(&backend, "cursor", &before, remove("b")).unwrap();
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn disabled_flag_entries_count_as_existing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cline_mcp_settings.json");
+        let backend = JsonBackend::new("cline", Some(path.to_str().unwrap())).unwrap();
+        let before = set(json!({}), json!({"b": {"command": "b"}}));
+
+        assert!(apply(&backend, "cline", &before, add("b", "new")).is_err());
+        let after = apply(&backend, "cline", &before, remove("b")).unwrap();
+        assert!(!after.contains("b"));
+    }
+
+    #[test]
+    fn codex_toggles_leave_missing_and_settled_servers_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = CodexBackend::new(Some(dir.path().to_str().unwrap())).unwrap();
+        let before = set(
+            json!({"a": {"command": "a"}}),
+            json!({"b": {"command": "b"}}),
+        );
+        let disable = |name: &str| ServerChange::Disable {
+            name: name.to_string(),
+        };
+        let enable = |name: &str| ServerChange::Enable {
+            name: name.to_string(),
+        };
+
+        // The backend reports these as not found or already in state
+        for change in [
+            disable("b"),
+            disable("missing"),
+            enable("a"),
+            enable("missing"),
+        ] {
+            assert_eq!(apply(&backend, "codex", &before, change).unwrap(), before);
+        }
+
+        let after = apply(&backend, "codex", &before, disable("a")).unwrap();
+        assert!(after.disabled.contains_key("a"));
+        let after = apply(&backend, "codex", &before, enable("b")).unwrap();
+        assert!(after.active.contains_key("b"));
+
+        let path = dir.path().join("mcp.json");
+        let json = JsonBackend::new("cursor", Some(path.to_str().unwrap())).unwrap();
+        assert!(apply(&json, "cursor", &before, disable("missing")).is_err());
+    }
+}
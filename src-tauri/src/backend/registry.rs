@@ -0,0 +1,65 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+use super::ClientBackend;
+use super::claude_code::ClaudeCodeBackend;
+use super::codex::CodexBackend;
+use super::json::JsonBackend;
+
+type BackendFactory = fn(&str, Option<&str>) -> Result<Box<dyn ClientBackend>, String>;
+
+fn json_backend(client: &str, path: Option<&str>) -> Result<Box<dyn ClientBackend>, String> {
+    Ok(Box::new(JsonBackend::new(client, path)?))
+}
+
+fn codex_backend(_client: &str, path: Option<&str>) -> Result<Box<dyn ClientBackend>, String> {
+    Ok(Box::new(CodexBackend::new(path)?))
+}
+
+fn claude_code_backend(
+    _client: &str,
+    path: Option<&str>,
+) -> Result<Box<dyn ClientBackend>, String> {
+    let working_dir = path
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "Claude Code workingDir is required".to_string())?;
+    Ok(Box::new(ClaudeCodeBackend::new(working_dir)))
+}
+
+// Clients that are not listed here go through the document backend as well:
+// user-defined clients from descriptor files and custom paths chosen in the UI.
+static BACKENDS: Lazy<HashMap<&'static str, BackendFactory>> = Lazy::new(|| {
+    let mut m: HashMap<&'static str, BackendFactory> = HashMap::new();
+    m.insert("codex", codex_backend);
+    m.insert("claude_code", claude_code_backend);
+    for client in [
+        "claude",
+        "cline",
+        "roo_code",
+        "copilot",
+        "cursor",
+        "mcphub",
+        "windsurf",
+        "mcplinker",
+    ] {
+        m.insert(client, json_backend);
+    }
+    m
+});
+
+pub fn is_builtin_client(client: &str) -> bool {
+    BACKENDS.contains_key(client)
+}
+
+/// Ids of all built-in clients, sorted
+pub fn builtin_clients() -> Vec<&'static str> {
+    let mut clients: Vec<&'static str> = BACKENDS.keys().copied().collect();
+    clients.sort();
+    clients
+}
+
+/// Look up the backend for a client id
+pub fn get_backend(client: &str, path: Option<&str>) -> Result<Box<dyn ClientBackend>, String> {
+    let factory = BACKENDS.get(client).copied().unwrap_or(json_backend);
+    factory(client, path)
+}
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::client_descriptor::get_descriptor;
+
+/// Transport a server entry connects over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    Stdio,
+    Sse,
+    #[serde(alias = "streamable_http", alias = "streamable-http")]
+    Http,
+}
+
+impl Transport {
+    pub const ALL: [Transport; 3] = [Transport::Stdio, Transport::Sse, Transport::Http];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Transport::Stdio => "stdio",
+            Transport::Sse => "sse",
+            Transport::Http => "http",
+        }
+    }
+
+    /// Detect the transport of a server entry from its `type` or its fields
+    pub fn of(config: &Value) -> Option<Transport> {
+        match config.get("type").and_then(|v| v.as_str()) {
+            Some("stdio") => Some(Transport::Stdio),
+            Some("sse") => Some(Transport::Sse),
+            Some("http" | "streamable-http" | "streamable_http" | "streamableHttp") => {
+                Some(Transport::Http)
+            }
+            Some(_) => None,
+            None if config.get("command").is_some() => Some(Transport::Stdio),
+            None if config.get("url").is_some() || config.get("serverUrl").is_some() => {
+                Some(Transport::Http)
+            }
+            None => None,
+        }
+    }
+}
+
+/// Transports each client can run. Custom clients declare theirs in their
+/// descriptor; clients not listed here take all of them.
+pub fn supported_transports(client: &str) -> Vec<Transport> {
+    if let Some(descriptor) = get_descriptor(client) {
+        return descriptor.transports;
+    }
+    match client {
+        // Claude Desktop only starts local processes from its config file
+        "claude" => vec![Transport::Stdio],
+        // Codex has no SSE client
+        "codex" => vec![Transport::Stdio, Transport::Http],
+        _ => Transport::ALL.to_vec(),
+    }
+}
+
+/// Run a remote server through a local `npx mcp-remote <url>` bridge
+fn wrap_in_mcp_remote(config: &Value, transport: Transport) -> Option<Value> {
+    let url = config
+        .get("url")
+        .or_else(|| config.get("serverUrl"))
+        .and_then(|v| v.as_str())?;
+
+    let mut args = vec!["-y".to_string(), "mcp-remote".to_string(), url.to_string()];
+    let headers = config.get("headers").or_else(|| config.get("http_headers"));
+    if let Some(headers) = headers.and_then(|v| v.as_object()) {
+        for (name, value) in headers {
+            let value = value
+                .as_str()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| value.to_string());
+            args.push("--header".to_string());
+            args.push(format!("{}: {}", name, value));
+        }
+    }
+    args.push("--transport".to_string());
+    args.push(
+        match transport {
+            Transport::Sse => "sse-only",
+            _ => "http-only",
+        }
+        .to_string(),
+    );
+
+    let mut wrapped = json!({ "command": "npx", "args": args });
+    if let Some(env) = config.get("env") {
+        wrapped["env"] = env.clone();
+    }
+    Some(wrapped)
+}
+
+/// Fit a server entry to the transports a client supports: unchanged when it
+/// can run it, bridged to stdio when the client only lacks a remote transport,
+/// rejected otherwise. Entries of an unknown transport are left to the client.
+pub fn adapt(client: &str, supported: &[Transport], config: Value) -> Result<Value, String> {
+    let Some(transport) = Transport::of(&config) else {
+        return Ok(config);
+    };
+    if supported.contains(&transport) {
+        return Ok(config);
+    }
+
+    let unsupported = || {
+        format!(
+            "Client '{}' does not support {} servers",
+            client,
+            transport.as_str()
+        )
+    };
+    if transport == Transport::Stdio || !supported.contains(&Transport::Stdio) {
+        return Err(format!("{} and no adaptation is available", unsupported()));
+    }
+    let wrapped = wrap_in_mcp_remote(&config, transport)
+        .ok_or_else(|| format!("{}, and the server has no url to bridge", unsupported()))?;
+    println!(
+        "[Transport] {} server bridged through mcp-remote for {}",
+        transport.as_str(),
+        client
+    );
+    Ok(wrapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_the_transport_from_type_or_fields() {
+        assert_eq!(
+            Transport::of(&json!({"command": "npx"})),
+            Some(Transport::Stdio)
+        );
+        assert_eq!(
+            Transport::of(&json!({"serverUrl": "https://x"})),
+            Some(Transport::Http)
+        );
+        assert_eq!(
+            Transport::of(&json!({"type": "sse", "url": "https://x"})),
+            Some(Transport::Sse)
+        );
+        assert_eq!(Transport::of(&json!({"type": "websocket"})), None);
+    }
+
+    #[test]
+    fn bridges_remote_servers_through_mcp_remote() {
+        let config = json!({
+            "type": "sse",
+            "url": "https://example.com/sse",
+            "headers": {"Authorization": "Bearer t"},
+            "env": {"K": "v"}
+        });
+        let bridged = adapt("claude", &[Transport::Stdio], config).unwrap();
+        assert_eq!(
+            bridged,
+            json!({
+                "command": "npx",
+                "args": [
+                    "-y", "mcp-remote", "https://example.com/sse",
+                    "--header", "Authorization: Bearer t",
+                    "--transport", "sse-only"
+                ],
+                "env": {"K": "v"}
+            })
+        );
+
+        let http = json!({"type": "http", "url": "https://example.com/mcp"});
+        let bridged = adapt("claude", &[Transport::Stdio], http).unwrap();
+        assert_eq!(bridged["args"][3], "--transport");
+        assert_eq!(bridged["args"][4], "http-only");
+    }
+
+    #[test]
+    fn keeps_supported_entries_and_rejects_the_rest() {
+        let sse = json!({"type": "sse", "url": "https://example.com/sse"});
+        assert_eq!(adapt("cursor", &Transport::ALL, sse.clone()).unwrap(), sse);
+        let unknown = json!({"type": "websocket"});
+        assert_eq!(
+            adapt("claude", &[Transport::Stdio], unknown.clone()).unwrap(),
+            unknown
+        );
+
+        // Without stdio there is nothing to bridge through
+        let err = adapt("remote-only", &[Transport::Http], sse).unwrap_err();
+        assert!(err.contains("does not support sse"));
+        let err = adapt("remote-only", &[Transport::Http], json!({"command": "npx"})).unwrap_err();
+        assert!(err.contains("no adaptation"));
+        let no_url = json!({"type": "sse"});
+        assert!(
+            adapt("claude", &[Transport::Stdio], no_url)
+                .unwrap_err()
+                .contains("no url")
+        );
+    }
+}
@@ -0,0 +1,557 @@
+use chrono::{DateTime, Duration, Utc};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::task;
+
+use crate::{atomic_write, config_lock};
+
+// Before mcp-linker changes a client config, the previous contents are copied
+// into a per-client store:
+//
+//   ~/.config/mcplinker/backups/retention.json     RetentionPolicy
+//   ~/.config/mcplinker/backups/<client>/<id>.bak  previous contents
+//   ~/.config/mcplinker/backups/<client>/<id>.json BackupEntry
+//
+// Ids start with a UTC timestamp, so they sort oldest first. The metadata is
+// written last, which keeps half-written snapshots out of listings.
+
+const DIFF_CONTEXT: usize = 3;
+// Above this many line pairs the diff stops looking for common lines. The
+// table holds one u32 per pair, so a million pairs is about 4MB.
+const DIFF_MAX_CELLS: usize = 1_000_000;
+
+/// A change about to be made to a client config
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub client: String,
+    pub name: String,
+}
+
+impl Operation {
+    pub fn new(client: &str, name: &str) -> Self {
+        Self {
+            client: client.to_string(),
+            name: name.to_string(),
+        }
+    }
+}
+
+/// One snapshot of a config file, taken right before an operation replaced it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupEntry {
+    pub id: String,
+    pub client: String,
+    pub operation: String,
+    pub source_path: String,
+    pub created_at: DateTime<Utc>,
+    pub size: u64,
+}
+
+/// How many snapshots are kept for each client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    /// Newest snapshots kept per client; 0 turns backups off
+    pub max_per_client: usize,
+    /// Snapshots older than this are dropped; `None` keeps them regardless of age
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_per_client: 20,
+            max_age_days: Some(30),
+        }
+    }
+}
+
+/// A snapshot compared with the file it was taken from
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupDiff {
+    pub entry: BackupEntry,
+    pub current_exists: bool,
+    pub identical: bool,
+    /// Unified diff from the snapshot to the current file
+    pub diff: String,
+}
+
+fn get_backups_dir() -> Result<PathBuf, String> {
+    let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    Ok(home.join(".config/mcplinker/backups"))
+}
+
+fn client_dir(client: &str) -> Result<PathBuf, String> {
+    if client.is_empty()
+        || client.starts_with('.')
+        || !client
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err(format!("Invalid client name for backups: '{}'", client));
+    }
+    Ok(get_backups_dir()?.join(client))
+}
+
+fn entry_paths(client: &str, id: &str) -> Result<(PathBuf, PathBuf), String> {
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        || id.starts_with('.')
+    {
+        return Err(format!("Invalid backup id: '{}'", id));
+    }
+    let dir = client_dir(client)?;
+    Ok((
+        dir.join(format!("{}.bak", id)),
+        dir.join(format!("{}.json", id)),
+    ))
+}
+
+/// Load the retention policy, falling back to the defaults
+pub fn load_retention() -> RetentionPolicy {
+    let Ok(path) = get_backups_dir().map(|dir| dir.join("retention.json")) else {
+        return RetentionPolicy::default();
+    };
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            println!("[Backup] Ignoring invalid {}: {}", path.display(), e);
+            RetentionPolicy::default()
+        }),
+        Err(_) => RetentionPolicy::default(),
+    }
+}
+
+fn save_retention(policy: &RetentionPolicy) -> Result<(), String> {
+    let path = get_backups_dir()?.join("retention.json");
+    let content = serde_json::to_string_pretty(policy)
+        .map_err(|e| format!("Failed to serialize retention policy: {}", e))?;
+    atomic_write::write_file_blocking(&path, content.as_bytes())
+}
+
+/// Save `previous`, the contents of `path` about to be replaced by `next`.
+/// Nothing is saved for a new file, an unchanged one, or when backups are off.
+pub fn snapshot_blocking(
+    operation: &Operation,
+    path: &Path,
+    previous: Option<&str>,
+    next: &str,
+) -> Result<Option<BackupEntry>, String> {
+    let Some(previous) = previous else {
+        return Ok(None);
+    };
+    let policy = load_retention();
+    if previous == next || policy.max_per_client == 0 {
+        return Ok(None);
+    }
+
+    let dir = client_dir(&operation.client)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let created_at = Utc::now();
+    let base_id = format!(
+        "{}-{}",
+        created_at.format("%Y%m%dT%H%M%S%3fZ"),
+        operation.name
+    );
+    // Two snapshots in the same millisecond get a counter
+    let mut id = base_id.clone();
+    let mut n = 1;
+    while dir.join(format!("{}.json", id)).exists() {
+        id = format!("{}-{}", base_id, n);
+        n += 1;
+    }
+    let (data_path, meta_path) = entry_paths(&operation.client, &id)?;
+
+    let entry = BackupEntry {
+        id,
+        client: operation.client.clone(),
+        operation: operation.name.clone(),
+        source_path: path.to_string_lossy().to_string(),
+        created_at,
+        size: previous.len() as u64,
+    };
+    let meta = serde_json::to_string_pretty(&entry)
+        .map_err(|e| format!("Failed to serialize backup metadata: {}", e))?;
+    atomic_write::write_file_blocking(&data_path, previous.as_bytes())
+        .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+    atomic_write::write_file_blocking(&meta_path, meta.as_bytes())
+        .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+    println!(
+        "[Backup] Saved {} before {} ({})",
+        path.display(),
+        operation.name,
+        entry.id
+    );
+
+    if let Err(e) = prune(&operation.client, &policy) {
+        println!(
+            "[Backup] Failed to prune {} backups: {}",
+            operation.client, e
+        );
+    }
+    Ok(Some(entry))
+}
+
+/// Async wrapper around [`snapshot_blocking`]
+pub async fn snapshot(
+    operation: Operation,
+    path: &Path,
+    previous: Option<String>,
+    next: String,
+) -> Result<Option<BackupEntry>, String> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || snapshot_blocking(&operation, &path, previous.as_deref(), &next))
+        .await
+        .map_err(|e| format!("Failed to run blocking task for backup: {}", e))?
+}
+
+/// Drop a snapshot whose operation ended up not writing anything
+pub fn discard(entry: &BackupEntry) {
+    if let Ok((data_path, meta_path)) = entry_paths(&entry.client, &entry.id) {
+        let _ = fs::remove_file(meta_path);
+        let _ = fs::remove_file(data_path);
+    }
+}
+
+/// Snapshots of one client, newest first
+pub fn list_entries(client: &str) -> Result<Vec<BackupEntry>, String> {
+    let dir = client_dir(client)?;
+    let read_dir = match fs::read_dir(&dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read backup directory: {}", e)),
+    };
+
+    let mut entries: Vec<BackupEntry> = read_dir
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|p| {
+            let content = fs::read_to_string(&p).ok()?;
+            match serde_json::from_str::<BackupEntry>(&content) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    println!("[Backup] Skipping {}: {}", p.display(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+    Ok(entries)
+}
+
+fn list_clients() -> Result<Vec<String>, String> {
+    let dir = get_backups_dir()?;
+    let read_dir = match fs::read_dir(&dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read backup directory: {}", e)),
+    };
+    let mut clients: Vec<String> = read_dir
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    clients.sort();
+    Ok(clients)
+}
+
+/// Remove snapshots of `client` that fall outside `policy`
+fn prune(client: &str, policy: &RetentionPolicy) -> Result<(), String> {
+    let cutoff = policy
+        .max_age_days
+        .map(|days| Utc::now() - Duration::days(days as i64));
+    for (index, entry) in list_entries(client)?.iter().enumerate() {
+        let too_many = index >= policy.max_per_client;
+        let too_old = cutoff.is_some_and(|cutoff| entry.created_at < cutoff);
+        if too_many || too_old {
+            discard(entry);
+        }
+    }
+    Ok(())
+}
+
+fn read_entry(client: &str, id: &str) -> Result<(BackupEntry, String), String> {
+    let (data_path, meta_path) = entry_paths(client, id)?;
+    let meta = fs::read_to_string(&meta_path)
+        .map_err(|_| format!("Backup '{}' not found for client '{}'", id, client))?;
+    let entry: BackupEntry = serde_json::from_str(&meta)
+        .map_err(|e| format!("Failed to parse backup metadata: {}", e))?;
+    let contents =
+        fs::read_to_string(&data_path).map_err(|e| format!("Failed to read backup: {}", e))?;
+    Ok((entry, contents))
+}
+
+/// Line diff of `old` against `new` with a few lines of context around each change
+fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old_lines, &new_lines);
+    if ops.iter().all(|op| matches!(op, DiffOp::Equal(..))) {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+
+    // Group changes whose context overlaps into one hunk
+    let mut start = 0;
+    while start < changed.len() {
+        let mut end = start;
+        while end + 1 < changed.len() && changed[end + 1] - changed[end] <= 2 * DIFF_CONTEXT + 1 {
+            end += 1;
+        }
+        let from = changed[start].saturating_sub(DIFF_CONTEXT);
+        let to = (changed[end] + DIFF_CONTEXT + 1).min(ops.len());
+        let hunk = &ops[from..to];
+
+        let (old_start, new_start) = ops[..from].iter().fold((0, 0), |(o, n), op| match op {
+            DiffOp::Equal(..) => (o + 1, n + 1),
+            DiffOp::Delete(_) => (o + 1, n),
+            DiffOp::Insert(_) => (o, n + 1),
+        });
+        let old_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Insert(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Delete(_)))
+            .count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            if old_len == 0 {
+                old_start
+            } else {
+                old_start + 1
+            },
+            old_len,
+            if new_len == 0 {
+                new_start
+            } else {
+                new_start + 1
+            },
+            new_len
+        ));
+        for op in hunk {
+            match op {
+                DiffOp::Equal(line) => out.push_str(&format!(" {}\n", line)),
+                DiffOp::Delete(line) => out.push_str(&format!("-{}\n", line)),
+                DiffOp::Insert(line) => out.push_str(&format!("+{}\n", line)),
+            }
+        }
+        start = end + 1;
+    }
+    out
+}
+
+#[derive(Debug, PartialEq)]
+enum DiffOp<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+fn diff_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffOp<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<DiffOp> = old[..prefix].iter().map(|l| DiffOp::Equal(l)).collect();
+    if old_mid.len().saturating_mul(new_mid.len()) > DIFF_MAX_CELLS {
+        // Too big to align; show the changed block as a whole
+        ops.extend(old_mid.iter().map(|l| DiffOp::Delete(l)));
+        ops.extend(new_mid.iter().map(|l| DiffOp::Insert(l)));
+    } else {
+        // Longest common subsequence, walked from the front
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let width = m + 1;
+        let mut table = vec![0u32; (n + 1) * width];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                table[i * width + j] = if old_mid[i] == new_mid[j] {
+                    table[(i + 1) * width + j + 1] + 1
+                } else {
+                    table[(i + 1) * width + j].max(table[i * width + j + 1])
+                };
+            }
+        }
+        let lcs = |i: usize, j: usize| table[i * width + j];
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                ops.push(DiffOp::Equal(old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs(i + 1, j) >= lcs(i, j + 1)) {
+                ops.push(DiffOp::Delete(old_mid[i]));
+                i += 1;
+            } else {
+                ops.push(DiffOp::Insert(new_mid[j]));
+                j += 1;
+            }
+        }
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|l| DiffOp::Equal(l)));
+    ops
+}
+
+/// List snapshots of one client, or of every client when none is given
+#[tauri::command]
+pub async fn list_backups(client_name: Option<String>) -> Result<Vec<BackupEntry>, String> {
+    match client_name {
+        Some(client) => list_entries(&client),
+        None => {
+            let mut entries = Vec::new();
+            for client in list_clients()? {
+                entries.extend(list_entries(&client)?);
+            }
+            entries.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+            Ok(entries)
+        }
+    }
+}
+
+/// Compare a snapshot with the current contents of the file it came from
+#[tauri::command]
+pub async fn diff_backup(client_name: String, backup_id: String) -> Result<BackupDiff, String> {
+    let (entry, contents) = read_entry(&client_name, &backup_id)?;
+    let source = PathBuf::from(&entry.source_path);
+    let current = match fs::read_to_string(&source) {
+        Ok(current) => Some(current),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Failed to read {}: {}", source.display(), e)),
+    };
+
+    let diff = unified_diff(
+        &contents,
+        current.as_deref().unwrap_or(""),
+        &format!("{} ({})", entry.source_path, entry.id),
+        &entry.source_path,
+    );
+    Ok(BackupDiff {
+        current_exists: current.is_some(),
+        identical: current.as_deref() == Some(contents.as_str()),
+        diff,
+        entry,
+    })
+}
+
+/// Put a snapshot back in place. The contents it replaces are snapshotted
+/// first, so a restore can itself be undone.
+#[tauri::command]
+pub async fn restore_backup(
+    client_name: String,
+    backup_id: String,
+) -> Result<Option<BackupEntry>, String> {
+    let (entry, contents) = read_entry(&client_name, &backup_id)?;
+    let source = PathBuf::from(&entry.source_path);
+
+    let _lock = config_lock::lock(&source).await?;
+    let (current, _) = config_lock::read_with_fingerprint(&source).await?;
+    let undo = snapshot(
+        Operation::new(&entry.client, "restore"),
+        &source,
+        current,
+        contents.clone(),
+    )
+    .await?;
+    atomic_write::write_file(&source, contents.into_bytes()).await?;
+    println!("[Backup] Restored {} from {}", source.display(), entry.id);
+    Ok(undo)
+}
+
+#[tauri::command]
+pub async fn get_backup_retention() -> Result<RetentionPolicy, String> {
+    Ok(load_retention())
+}
+
+/// Save the retention policy and apply it to the existing snapshots
+#[tauri::command]
+pub async fn set_backup_retention(policy: RetentionPolicy) -> Result<(), String> {
+    save_retention(&policy)?;
+    for client in list_clients()? {
+        prune(&client, &policy)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DiffOp::*;
+
+    #[test]
+    fn aligns_common_lines() {
+        let old = ["a", "b", "c", "d"];
+        let new = ["a", "x", "c", "d", "e"];
+        assert_eq!(
+            diff_ops(&old, &new),
+            [
+                Equal("a"),
+                Delete("b"),
+                Insert("x"),
+                Equal("c"),
+                Equal("d"),
+                Insert("e")
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_lines_that_moved_past_a_change() {
+        let old = ["a", "b", "c"];
+        let new = ["b", "c", "a"];
+        assert_eq!(
+            diff_ops(&old, &new),
+            [Delete("a"), Equal("b"), Equal("c"), Insert("a")]
+        );
+    }
+
+    #[test]
+    fn shows_an_oversized_change_as_one_block() {
+        let old: Vec<String> = (0..1100).map(|i| format!("old {}", i)).collect();
+        let new: Vec<String> = (0..1100).map(|i| format!("new {}", i)).collect();
+        let mut old: Vec<&str> = old.iter().map(String::as_str).collect();
+        let mut new: Vec<&str> = new.iter().map(String::as_str).collect();
+        old.insert(0, "same");
+        new.insert(0, "same");
+
+        let ops = diff_ops(&old, &new);
+        assert_eq!(ops[0], Equal("same"));
+        assert!(ops[1..1101].iter().all(|op| matches!(op, Delete(_))));
+        assert!(ops[1101..].iter().all(|op| matches!(op, Insert(_))));
+    }
+
+    #[test]
+    fn writes_hunks_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n";
+        assert_eq!(
+            unified_diff(old, new, "before", "after"),
+            "--- before\n+++ after\n\
+             @@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n\
+             @@ -8,3 +8,4 @@\n 8\n 9\n 10\n+11\n"
+        );
+        assert_eq!(unified_diff(old, old, "before", "after"), "");
+    }
+}
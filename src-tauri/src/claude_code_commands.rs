@@ -3,12 +3,79 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::command;
 
+use crate::backend::Transport;
+use crate::backend::transport::supported_transports;
+use crate::backup::Operation;
+use crate::json_manager::file_io::{ConfigFormat, edit_config_file};
+
 // ~/.claude.json {projects: { "working_dir": "mcpServers": server}, other_keys: {}}
 // {'sentry': {'type': 'http', 'url': 'https://mcp.sentry.dev/mcp'},
 //  'airtable': {'type': 'stdio', 'command': 'npx', 'args': ['-y', 'airtable-mcp-server'], 'env': {'AIRTABLE_API_KEY': 'YOUR_KEY'}}}
+//
+// Claude Code reads servers from three scopes, and a server in a scope hides
+// one of the same name further down:
+//   local:   projects.<working_dir>.mcpServers in ~/.claude.json
+//   project: mcpServers in <working_dir>/.mcp.json, committed with the project
+//   user:    mcpServers at the top of ~/.claude.json, for every project
+//
+// Claude Code asks before starting a server from a project's .mcp.json and
+// keeps the answers in projects.<working_dir> of ~/.claude.json:
+// enabledMcpjsonServers, disabledMcpjsonServers and enableAllProjectMcpServers.
+
+/// Where a Claude Code server is configured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaudeScope {
+    Local,
+    Project,
+    User,
+}
+
+impl ClaudeScope {
+    /// Highest precedence first
+    pub const ALL: [ClaudeScope; 3] = [ClaudeScope::Local, ClaudeScope::Project, ClaudeScope::User];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClaudeScope::Local => "local",
+            ClaudeScope::Project => "project",
+            ClaudeScope::User => "user",
+        }
+    }
+}
+
+/// Whether Claude Code starts a server from a project's `.mcp.json`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpjsonApproval {
+    Approved,
+    Rejected,
+    /// Claude Code will ask on the next start
+    Pending,
+}
+
+/// The approval lists of one project
+#[derive(Debug, Default)]
+struct McpjsonApprovals {
+    enabled: Vec<String>,
+    disabled: Vec<String>,
+    enable_all: bool,
+}
+
+impl McpjsonApprovals {
+    fn of(&self, name: &str) -> McpjsonApproval {
+        // A rejection wins over approving everything
+        if self.disabled.iter().any(|n| n == name) {
+            McpjsonApproval::Rejected
+        } else if self.enable_all || self.enabled.iter().any(|n| n == name) {
+            McpjsonApproval::Approved
+        } else {
+            McpjsonApproval::Pending
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ClaudeCodeServer {
@@ -18,6 +85,17 @@ pub struct ClaudeCodeServer {
     pub command: Option<String>,
     pub args: Option<Vec<String>>,
     pub env: Option<HashMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HashMap<String, String>>,
+    /// The scope the server was read from, or is written to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<ClaudeScope>,
+    /// The nearer scope whose server of the same name hides this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overridden_by: Option<ClaudeScope>,
+    /// Set for servers of the project scope
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<McpjsonApproval>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -26,44 +104,46 @@ pub struct ClaudeCodeResponse {
     pub message: String,
 }
 
-/// List all MCP servers configured in Claude Code
+/// List the MCP servers of a project, in order of precedence. Without a
+/// scope, servers of every scope are listed, hidden ones with `overridden_by`.
+/// Rejected `.mcp.json` servers are left out, as Claude Code doesn't start them.
 #[command]
-pub async fn claude_mcp_list(working_dir: String) -> Result<Vec<ClaudeCodeServer>, String> {
-    let claude_config_path = get_claude_config_path(Some(working_dir.clone()))?;
-
-    if !claude_config_path.exists() {
-        return Ok(Vec::new());
-    }
-
-    let config_content = fs::read_to_string(&claude_config_path)
-        .map_err(|e| format!("Failed to read Claude config: {}", e))?;
-
-    let config: serde_json::Value = serde_json::from_str(&config_content)
-        .map_err(|e| format!("Failed to parse Claude config: {}", e))?;
-
+pub async fn claude_mcp_list(
+    working_dir: String,
+    scope: Option<ClaudeScope>,
+) -> Result<Vec<ClaudeCodeServer>, String> {
     let mut servers = Vec::new();
-
-    if let Some(projects) = config.get("projects") {
-        if let Some(project_config) = projects.get(&working_dir) {
-            if let Some(mcp_servers) = project_config.get("mcpServers") {
-                if let Some(servers_obj) = mcp_servers.as_object() {
-                    for (name, server_config) in servers_obj {
-                        if let Ok(server) = parse_server_config(name, server_config) {
-                            servers.push(server);
-                        }
-                    }
-                }
+    let mut seen: HashMap<String, ClaudeScope> = HashMap::new();
+    for current in ClaudeScope::ALL {
+        for (name, server_config) in active_scope_servers(&working_dir, current)? {
+            if let Ok(mut server) = parse_server_config(&name, &server_config) {
+                server.scope = Some(current);
+                server.overridden_by = seen.get(&name).copied();
+                seen.entry(name).or_insert(current);
+                servers.push(server);
             }
         }
+        // Scopes below the one asked for can't hide it
+        if scope == Some(current) {
+            break;
+        }
     }
 
+    if let Some(scope) = scope {
+        servers.retain(|s| s.scope == Some(scope));
+    }
     Ok(servers)
 }
 
-/// Get details for a specific MCP server
+/// Get details for a specific MCP server: the one Claude Code uses, or the
+/// one in `scope`
 #[command]
-pub async fn claude_mcp_get(name: String, working_dir: String) -> Result<ClaudeCodeServer, String> {
-    let servers = claude_mcp_list(working_dir).await?;
+pub async fn claude_mcp_get(
+    name: String,
+    working_dir: String,
+    scope: Option<ClaudeScope>,
+) -> Result<ClaudeCodeServer, String> {
+    let servers = claude_mcp_list(working_dir, scope).await?;
 
     servers
         .into_iter()
@@ -71,131 +151,176 @@ pub async fn claude_mcp_get(name: String, working_dir: String) -> Result<ClaudeC
         .ok_or_else(|| format!("Server '{}' not found", name))
 }
 
-/// Add a new MCP server to Claude Code
+/// Add a new MCP server to Claude Code, or replace one. Without a scope it
+/// goes where a server of that name already takes effect, or to local.
 #[command]
 pub async fn claude_mcp_add(
     request: ClaudeCodeServer,
     working_dir: String,
+    scope: Option<ClaudeScope>,
 ) -> Result<ClaudeCodeResponse, String> {
-    let claude_config_path = get_claude_config_path(Some(working_dir.clone()))?;
-
-    // Create backup if config file exists
-    let backup_path = if claude_config_path.exists() {
-        Some(create_backup(&claude_config_path)?)
-    } else {
-        None
-    };
-
-    // Read existing config or create new one
-    let mut config: serde_json::Value = if claude_config_path.exists() {
-        let config_content = fs::read_to_string(&claude_config_path)
-            .map_err(|e| format!("Failed to read Claude config: {}", e))?;
-        serde_json::from_str(&config_content)
-            .map_err(|e| format!("Failed to parse Claude config: {}", e))?
-    } else {
-        serde_json::json!({"projects": {}})
-    };
-
-    // Get or create the current working directory entry
-    let current_dir = working_dir;
-
-    if !config["projects"].is_object() {
-        config["projects"] = serde_json::json!({});
-    }
-
-    if !config["projects"][&current_dir].is_object() {
-        config["projects"][&current_dir] = serde_json::json!({"mcpServers": {}});
-    }
-
-    if !config["projects"][&current_dir]["mcpServers"].is_object() {
-        config["projects"][&current_dir]["mcpServers"] = serde_json::json!({});
-    }
-
     // Convert server to JSON format
     let server_json = server_to_json(&request)?;
-    config["projects"][&current_dir]["mcpServers"][&request.name] = server_json;
-
-    // Write back to file
-    if let Err(e) = fs::write(
-        &claude_config_path,
-        serde_json::to_string_pretty(&config).unwrap(),
-    ) {
-        // Restore backup if write fails
-        if let Some(backup_path) = &backup_path {
-            let _ = restore_backup(&claude_config_path, backup_path);
-        }
-        return Err(format!("Failed to write Claude config: {}", e));
-    }
+    check_server(&request, &server_json)?;
 
-    // Clean up backup file on success
-    if let Some(backup_path) = backup_path {
-        let _ = fs::remove_file(backup_path);
-    }
+    let scope = match scope.or(request.scope) {
+        Some(scope) => scope,
+        None => effective_scope(&working_dir, &request.name)?.unwrap_or(ClaudeScope::Local),
+    };
+    edit_scope(&working_dir, scope, "add_server", |servers| {
+        servers.insert(request.name.clone(), server_json.clone());
+        Ok(())
+    })
+    .await?;
 
     Ok(ClaudeCodeResponse {
         success: true,
-        message: format!("Server '{}' added successfully", request.name),
+        message: format!(
+            "Server '{}' added to {} scope",
+            request.name,
+            scope.as_str()
+        ),
     })
 }
 
-/// Remove an MCP server from Claude Code
+/// Remove an MCP server from Claude Code. Without a scope, the server Claude
+/// Code uses is removed, which can uncover one in a lower scope.
 #[command]
 pub async fn claude_mcp_remove(
     name: String,
     working_dir: String,
+    scope: Option<ClaudeScope>,
 ) -> Result<ClaudeCodeResponse, String> {
-    let claude_config_path = get_claude_config_path(Some(working_dir.clone()))?;
+    let scope = match scope {
+        Some(scope) => scope,
+        None => effective_scope(&working_dir, &name)?
+            .ok_or_else(|| format!("Server '{}' not found", name))?,
+    };
 
-    if !claude_config_path.exists() {
-        return Err("Claude config file not found".to_string());
-    }
+    edit_scope(&working_dir, scope, "remove_server", |servers| {
+        if servers.remove(&name).is_some() {
+            Ok(())
+        } else {
+            Err(format!("Server '{}' not found", name))
+        }
+    })
+    .await?;
 
-    // Create backup before making changes
-    let backup_path = create_backup(&claude_config_path)?;
+    Ok(ClaudeCodeResponse {
+        success: true,
+        message: format!("Server '{}' removed from {} scope", name, scope.as_str()),
+    })
+}
 
-    let config_content = fs::read_to_string(&claude_config_path)
-        .map_err(|e| format!("Failed to read Claude config: {}", e))?;
+/// Replace every MCP server of a project's local scope in one write
+pub(crate) async fn replace_project_servers(
+    working_dir: &str,
+    servers: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), String> {
+    let mut mcp_servers = serde_json::Map::new();
+    for (name, server_config) in servers {
+        let server = parse_server_config(name, server_config)?;
+        mcp_servers.insert(name.clone(), server_to_json(&server)?);
+    }
 
-    let mut config: serde_json::Value = serde_json::from_str(&config_content)
-        .map_err(|e| format!("Failed to parse Claude config: {}", e))?;
+    edit_scope(
+        working_dir,
+        ClaudeScope::Local,
+        "replace_servers",
+        |servers| {
+            *servers = mcp_servers.clone();
+            Ok(())
+        },
+    )
+    .await
+}
 
-    // Check if server exists in the specified working directory
-    let mut found = false;
-    if let Some(projects) = config.get_mut("projects") {
-        if let Some(project) = projects.get_mut(&working_dir) {
-            if let Some(mcp_servers) = project.get_mut("mcpServers") {
-                if let Some(servers_obj) = mcp_servers.as_object_mut() {
-                    if servers_obj.remove(&name).is_some() {
-                        found = true;
-                    }
-                }
-            }
+/// List the servers of a project's `.mcp.json` with their approval
+#[command]
+pub async fn claude_mcpjson_list(working_dir: String) -> Result<Vec<ClaudeCodeServer>, String> {
+    let approvals = read_approvals(&working_dir)?;
+    let mut servers = Vec::new();
+    for (name, server_config) in read_scope(&working_dir, ClaudeScope::Project)? {
+        if let Ok(mut server) = parse_server_config(&name, &server_config) {
+            server.scope = Some(ClaudeScope::Project);
+            server.approval = Some(approvals.of(&name));
+            servers.push(server);
         }
     }
+    Ok(servers)
+}
 
-    if found {
-        // Write back to file
-        if let Err(e) = fs::write(
-            &claude_config_path,
-            serde_json::to_string_pretty(&config).unwrap(),
-        ) {
-            // Restore backup if write fails
-            let _ = restore_backup(&claude_config_path, &backup_path);
-            return Err(format!("Failed to write Claude config: {}", e));
-        }
+/// Approve a `.mcp.json` server, so Claude Code starts it without asking
+#[command]
+pub async fn claude_mcpjson_approve(
+    working_dir: String,
+    name: String,
+) -> Result<Vec<ClaudeCodeServer>, String> {
+    set_approval(&working_dir, &name, McpjsonApproval::Approved).await?;
+    claude_mcpjson_list(working_dir).await
+}
 
-        // Clean up backup file on success
-        let _ = fs::remove_file(backup_path);
+/// Reject a `.mcp.json` server, so Claude Code doesn't start it. The entry
+/// stays in the committed file.
+#[command]
+pub async fn claude_mcpjson_reject(
+    working_dir: String,
+    name: String,
+) -> Result<Vec<ClaudeCodeServer>, String> {
+    set_approval(&working_dir, &name, McpjsonApproval::Rejected).await?;
+    claude_mcpjson_list(working_dir).await
+}
 
-        Ok(ClaudeCodeResponse {
-            success: true,
-            message: format!("Server '{}' removed successfully", name),
-        })
-    } else {
-        // Clean up backup file if server not found
-        let _ = fs::remove_file(backup_path);
-        Err(format!("Server '{}' not found", name))
+/// Approve every `.mcp.json` server of a project that isn't rejected, now and
+/// as they are added
+#[command]
+pub async fn claude_mcpjson_set_approve_all(
+    working_dir: String,
+    enabled: bool,
+) -> Result<Vec<ClaudeCodeServer>, String> {
+    let claude_config_path = get_claude_config_path(None)?;
+    edit_claude_config(&claude_config_path, "approve_all_mcpjson", |config| {
+        project_config_mut(config, &working_dir)["enableAllProjectMcpServers"] =
+            serde_json::Value::Bool(enabled);
+        Ok(())
+    })
+    .await?;
+    claude_mcpjson_list(working_dir).await
+}
+
+/// Move a `.mcp.json` server onto the approved or rejected list
+pub(crate) async fn set_approval(
+    working_dir: &str,
+    name: &str,
+    approval: McpjsonApproval,
+) -> Result<(), String> {
+    if !read_scope(working_dir, ClaudeScope::Project)?.contains_key(name) {
+        return Err(format!("Server '{}' not found in .mcp.json", name));
     }
+    let (add, drop) = match approval {
+        McpjsonApproval::Approved => ("enabledMcpjsonServers", "disabledMcpjsonServers"),
+        McpjsonApproval::Rejected => ("disabledMcpjsonServers", "enabledMcpjsonServers"),
+        McpjsonApproval::Pending => return Err("Only approve or reject can be set".to_string()),
+    };
+
+    let claude_config_path = get_claude_config_path(None)?;
+    edit_claude_config(&claude_config_path, "approve_mcpjson", |config| {
+        let project = project_config_mut(config, working_dir);
+        if let Some(list) = project[drop].as_array_mut() {
+            list.retain(|n| n.as_str() != Some(name));
+        }
+        if !project[add].is_array() {
+            project[add] = serde_json::json!([]);
+        }
+        let unlisted = project[add]
+            .as_array_mut()
+            .filter(|list| !list.iter().any(|n| n.as_str() == Some(name)));
+        if let Some(list) = unlisted {
+            list.push(serde_json::Value::from(name));
+        }
+        Ok(())
+    })
+    .await
 }
 
 /// List all projects configured in Claude Code
@@ -245,17 +370,182 @@ pub fn check_claude_config_exists() -> Result<bool, String> {
     Ok(Path::new(&path).exists())
 }
 
-fn get_claude_config_path(_working_dir: Option<String>) -> Result<PathBuf, String> {
+pub(crate) fn get_claude_config_path(_working_dir: Option<String>) -> Result<PathBuf, String> {
     let home_dir = dirs::home_dir().ok_or("Unable to find home directory")?;
     Ok(home_dir.join(".claude.json"))
 }
 
-fn parse_server_config(name: &str, config: &serde_json::Value) -> Result<ClaudeCodeServer, String> {
+/// The file a scope lives in
+fn scope_path(working_dir: &str, scope: ClaudeScope) -> Result<PathBuf, String> {
+    match scope {
+        ClaudeScope::Project => Ok(Path::new(working_dir).join(".mcp.json")),
+        ClaudeScope::Local | ClaudeScope::User => get_claude_config_path(None),
+    }
+}
+
+/// The servers of a scope inside its file, created when missing
+fn scope_servers_mut<'a>(
+    config: &'a mut serde_json::Value,
+    working_dir: &str,
+    scope: ClaudeScope,
+) -> Result<&'a mut serde_json::Map<String, serde_json::Value>, String> {
+    if !config.is_object() {
+        *config = serde_json::json!({});
+    }
+    let parent = match scope {
+        ClaudeScope::Local => project_config_mut(config, working_dir),
+        ClaudeScope::Project | ClaudeScope::User => config,
+    };
+    if !parent["mcpServers"].is_object() {
+        parent["mcpServers"] = serde_json::json!({});
+    }
+    parent["mcpServers"]
+        .as_object_mut()
+        .ok_or_else(|| "mcpServers is not an object".to_string())
+}
+
+/// A project's entry in `~/.claude.json`, created when missing
+fn project_config_mut<'a>(
+    config: &'a mut serde_json::Value,
+    working_dir: &str,
+) -> &'a mut serde_json::Value {
+    if !config.is_object() {
+        *config = serde_json::json!({});
+    }
+    if !config["projects"].is_object() {
+        config["projects"] = serde_json::json!({});
+    }
+    if !config["projects"][working_dir].is_object() {
+        config["projects"][working_dir] = serde_json::json!({});
+    }
+    &mut config["projects"][working_dir]
+}
+
+/// A JSON config file, or an empty object when there is none
+fn read_config(path: &Path) -> Result<serde_json::Value, String> {
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// The `.mcp.json` approvals of a project
+fn read_approvals(working_dir: &str) -> Result<McpjsonApprovals, String> {
+    let config = read_config(&get_claude_config_path(None)?)?;
+    let Some(project) = config.get("projects").and_then(|p| p.get(working_dir)) else {
+        return Ok(McpjsonApprovals::default());
+    };
+    let names = |key: &str| -> Vec<String> {
+        project
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|list| {
+                list.iter()
+                    .filter_map(|n| n.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    Ok(McpjsonApprovals {
+        enabled: names("enabledMcpjsonServers"),
+        disabled: names("disabledMcpjsonServers"),
+        enable_all: project
+            .get("enableAllProjectMcpServers")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    })
+}
+
+/// Servers of one scope, by name
+pub(crate) fn read_scope(
+    working_dir: &str,
+    scope: ClaudeScope,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let config = read_config(&scope_path(working_dir, scope)?)?;
+
+    let servers = match scope {
+        ClaudeScope::Local => config
+            .get("projects")
+            .and_then(|p| p.get(working_dir))
+            .and_then(|p| p.get("mcpServers")),
+        ClaudeScope::Project | ClaudeScope::User => config.get("mcpServers"),
+    };
+    Ok(servers
+        .and_then(|s| s.as_object())
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Servers of one scope Claude Code may start: all but rejected `.mcp.json` ones
+fn active_scope_servers(
+    working_dir: &str,
+    scope: ClaudeScope,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut servers = read_scope(working_dir, scope)?;
+    if scope == ClaudeScope::Project && !servers.is_empty() {
+        let approvals = read_approvals(working_dir)?;
+        servers.retain(|name, _| approvals.of(name) != McpjsonApproval::Rejected);
+    }
+    Ok(servers)
+}
+
+/// Rejected `.mcp.json` servers of a project, by name
+pub(crate) fn rejected_mcpjson(
+    working_dir: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut servers = read_scope(working_dir, ClaudeScope::Project)?;
+    if !servers.is_empty() {
+        let approvals = read_approvals(working_dir)?;
+        servers.retain(|name, _| approvals.of(name) == McpjsonApproval::Rejected);
+    }
+    Ok(servers)
+}
+
+/// The scope Claude Code takes a server from, if any has it
+pub(crate) fn effective_scope(
+    working_dir: &str,
+    name: &str,
+) -> Result<Option<ClaudeScope>, String> {
+    for scope in ClaudeScope::ALL {
+        if active_scope_servers(working_dir, scope)?.contains_key(name) {
+            return Ok(Some(scope));
+        }
+    }
+    Ok(None)
+}
+
+/// Edit the servers of one scope in place
+async fn edit_scope<T>(
+    working_dir: &str,
+    scope: ClaudeScope,
+    operation: &str,
+    mut edit: impl FnMut(&mut serde_json::Map<String, serde_json::Value>) -> Result<T, String>,
+) -> Result<T, String> {
+    let path = scope_path(working_dir, scope)?;
+    edit_claude_config(&path, operation, |config| {
+        edit(scope_servers_mut(config, working_dir, scope)?)
+    })
+    .await
+}
+
+pub(crate) fn parse_server_config(
+    name: &str,
+    config: &serde_json::Value,
+) -> Result<ClaudeCodeServer, String> {
+    // Determine type with stdio inference when command present
     let server_type = config
         .get("type")
         .and_then(|v| v.as_str())
-        .unwrap_or("stdio")
-        .to_string();
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| {
+            if config.get("command").is_none() && config.get("url").is_some() {
+                "http".to_string()
+            } else {
+                "stdio".to_string()
+            }
+        });
 
     let url = config
         .get("url")
@@ -273,11 +563,13 @@ fn parse_server_config(name: &str, config: &serde_json::Value) -> Result<ClaudeC
             .collect()
     });
 
-    let env = config.get("env").and_then(|v| v.as_object()).map(|obj| {
-        obj.iter()
-            .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
-            .collect()
-    });
+    let string_map = |key: &str| -> Option<HashMap<String, String>> {
+        config.get(key).and_then(|v| v.as_object()).map(|obj| {
+            obj.iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect()
+        })
+    };
 
     Ok(ClaudeCodeServer {
         name: name.to_string(),
@@ -285,11 +577,15 @@ fn parse_server_config(name: &str, config: &serde_json::Value) -> Result<ClaudeC
         url,
         command,
         args,
-        env,
+        env: string_map("env"),
+        headers: string_map("headers"),
+        scope: None,
+        overridden_by: None,
+        approval: None,
     })
 }
 
-fn server_to_json(server: &ClaudeCodeServer) -> Result<serde_json::Value, String> {
+pub(crate) fn server_to_json(server: &ClaudeCodeServer) -> Result<serde_json::Value, String> {
     let mut json = serde_json::json!({
         "type": server.r#type
     });
@@ -318,32 +614,131 @@ fn server_to_json(server: &ClaudeCodeServer) -> Result<serde_json::Value, String
         );
     }
 
+    if let Some(headers) = &server.headers {
+        json["headers"] = serde_json::Value::Object(
+            headers
+                .iter()
+                .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+                .collect(),
+        );
+    }
+
     Ok(json)
 }
 
-fn create_backup(config_path: &PathBuf) -> Result<PathBuf, String> {
-    if !config_path.exists() {
-        return Err("Config file does not exist".to_string());
+/// Reject entries Claude Code can't start: an unknown `type`, or one missing
+/// the field its transport needs
+fn check_server(server: &ClaudeCodeServer, server_json: &serde_json::Value) -> Result<(), String> {
+    let transport = Transport::of(server_json)
+        .ok_or_else(|| format!("Unsupported server type '{}'", server.r#type))?;
+    if !supported_transports("claude_code").contains(&transport) {
+        return Err(format!(
+            "Claude Code does not support {} servers",
+            transport.as_str()
+        ));
     }
+    match transport {
+        Transport::Stdio if server.command.is_none() => {
+            Err(format!("Server '{}' needs a command", server.name))
+        }
+        Transport::Sse | Transport::Http if server.url.is_none() => {
+            Err(format!("Server '{}' needs a url", server.name))
+        }
+        _ => Ok(()),
+    }
+}
 
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-
-    let backup_path = config_path.with_extension(format!("json.backup.{}", timestamp));
+/// Read-modify-write `~/.claude.json` or a `.mcp.json` under the config lock.
+/// Claude Code rewrites `~/.claude.json` constantly, so a change made between
+/// our read and our write makes `edit` run again on the fresh contents instead
+/// of being lost.
+async fn edit_claude_config<T>(
+    config_path: &Path,
+    operation: &str,
+    edit: impl FnMut(&mut serde_json::Value) -> Result<T, String>,
+) -> Result<T, String> {
+    let operation = Operation::new("claude_code", operation);
+    edit_config_file(config_path, ConfigFormat::Json, &operation, edit).await
+}
 
-    fs::copy(config_path, &backup_path).map_err(|e| format!("Failed to create backup: {}", e))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-    Ok(backup_path)
-}
+    fn server(name: &str, command: &str) -> ClaudeCodeServer {
+        parse_server_config(name, &json!({"type": "stdio", "command": command})).unwrap()
+    }
 
-fn restore_backup(config_path: &PathBuf, backup_path: &PathBuf) -> Result<(), String> {
-    if !backup_path.exists() {
-        return Err("Backup file does not exist".to_string());
+    async fn add(working_dir: &str, name: &str, command: &str, scope: ClaudeScope) {
+        claude_mcp_add(server(name, command), working_dir.to_string(), Some(scope))
+            .await
+            .unwrap();
     }
 
-    fs::copy(backup_path, config_path).map_err(|e| format!("Failed to restore backup: {}", e))?;
+    #[tokio::test]
+    async fn local_hides_project_which_hides_user() {
+        crate::test_home::init();
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path().display().to_string();
+        let name = "precedence-test";
+        add(&dir, name, "user", ClaudeScope::User).await;
+        add(&dir, name, "project", ClaudeScope::Project).await;
+        add(&dir, name, "local", ClaudeScope::Local).await;
+
+        let listed: Vec<(Option<ClaudeScope>, Option<ClaudeScope>)> =
+            claude_mcp_list(dir.clone(), None)
+                .await
+                .unwrap()
+                .into_iter()
+                .filter(|s| s.name == name)
+                .map(|s| (s.scope, s.overridden_by))
+                .collect();
+        assert_eq!(
+            listed,
+            [
+                (Some(ClaudeScope::Local), None),
+                (Some(ClaudeScope::Project), Some(ClaudeScope::Local)),
+                (Some(ClaudeScope::User), Some(ClaudeScope::Local)),
+            ]
+        );
+        let used = claude_mcp_get(name.to_string(), dir.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(used.command.as_deref(), Some("local"));
+
+        // Removing without a scope takes the server in use and uncovers the next
+        claude_mcp_remove(name.to_string(), dir.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            effective_scope(&dir, name).unwrap(),
+            Some(ClaudeScope::Project)
+        );
 
-    Ok(())
+        // A rejected .mcp.json server doesn't hide the user one
+        set_approval(&dir, name, McpjsonApproval::Rejected)
+            .await
+            .unwrap();
+        assert_eq!(
+            effective_scope(&dir, name).unwrap(),
+            Some(ClaudeScope::User)
+        );
+        let used = claude_mcp_get(name.to_string(), dir.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(used.command.as_deref(), Some("user"));
+
+        // Without a scope, an add goes where the server takes effect
+        claude_mcp_add(server(name, "updated"), dir.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            read_scope(&dir, ClaudeScope::User).unwrap()[name]["command"],
+            "updated"
+        );
+        claude_mcp_remove(name.to_string(), dir, Some(ClaudeScope::User))
+            .await
+            .unwrap();
+    }
 }
@@ -1,152 +1,435 @@
 use dirs::home_dir;
-use serde_json::{json, Value};
-use std::fs;
+use serde_json::{Map, Value, json};
 use std::path::PathBuf;
 use tauri::command;
 
-fn get_disabled_path() -> Result<PathBuf, String> {
+use crate::backup::Operation;
+use crate::claude_code_commands::{self, ClaudeScope, McpjsonApproval};
+use crate::json_manager::file_io::{ConfigFormat, edit_config_file, read_config_file};
+
+pub(crate) fn get_disabled_path() -> Result<PathBuf, String> {
     let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
     Ok(home.join(".claude.disabled.json"))
 }
 
-fn read_disabled_file() -> Result<Value, String> {
-    let path = get_disabled_path()?;
-    if !path.exists() {
-        return Ok(json!({"projects": {}}));
+/// Give the store its `projects` section when it lacks one
+fn ensure_projects(v: &mut Value) {
+    if !v["projects"].is_object() {
+        v["projects"] = json!({});
     }
-    let content = fs::read_to_string(&path).map_err(|e| format!("Read disabled file: {}", e))?;
-    let v: Value =
-        serde_json::from_str(&content).map_err(|e| format!("Parse disabled file: {}", e))?;
+}
+
+async fn read_disabled_file() -> Result<Value, String> {
+    let path = get_disabled_path()?;
+    let mut v = read_config_file(&path, ConfigFormat::Json).await?;
+    ensure_projects(&mut v);
     Ok(v)
 }
 
-fn write_disabled_file(v: &Value) -> Result<(), String> {
+/// Read-modify-write the disabled store under the config lock, replaying
+/// `edit` when the file changed between our read and our write
+async fn edit_disabled_file<T>(
+    operation: &str,
+    mut edit: impl FnMut(&mut Value) -> Result<T, String>,
+) -> Result<T, String> {
     let path = get_disabled_path()?;
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Create dir failed: {}", e))?;
-    }
-    fs::write(&path, serde_json::to_string_pretty(v).unwrap())
-        .map_err(|e| format!("Write disabled file: {}", e))
+    let operation = Operation::new("claude_code", operation);
+    edit_config_file(&path, ConfigFormat::Json, &operation, |v| {
+        ensure_projects(v);
+        edit(v)
+    })
+    .await
 }
 
-#[command]
-pub async fn claude_list_disabled(working_dir: String) -> Result<Value, String> {
-    let v = read_disabled_file()?;
-    Ok(v.get("projects")
-        .and_then(|p| p.get(&working_dir))
+/// Disabled servers of one project's local scope, as a map
+pub(crate) async fn read_project_disabled(working_dir: &str) -> Result<Map<String, Value>, String> {
+    let v = read_disabled_file().await?;
+    Ok(disabled_in(&v, working_dir, Some(ClaudeScope::Local))?
+        .as_object()
         .cloned()
-        .unwrap_or(json!({})))
+        .unwrap_or_default())
 }
 
-#[command]
-pub async fn claude_disable_server(working_dir: String, name: String) -> Result<Value, String> {
-    // Read current disabled and Claude config to fetch config for the named server
-    let mut disabled = read_disabled_file()?;
-    if !disabled["projects"].is_object() {
-        disabled["projects"] = json!({});
-    }
-    if !disabled["projects"][&working_dir].is_object() {
-        disabled["projects"][&working_dir] = json!({});
-    }
-
-    // Try to read from Claude config to copy server config
-    let servers = crate::claude_code_commands::claude_mcp_list(working_dir.clone()).await?;
-    if let Some(s) = servers.into_iter().find(|s| s.name == name) {
-        // Convert to JSON matching Manage shape
-        let mut cfg = json!({"type": s.r#type});
-        if let Some(url) = s.url {
-            cfg["url"] = json!(url);
+/// Replace the disabled servers of one project's local scope; an empty map removes the project
+pub(crate) async fn replace_project_disabled(
+    working_dir: &str,
+    servers: &Map<String, Value>,
+) -> Result<(), String> {
+    if servers.is_empty() && read_project_disabled(working_dir).await?.is_empty() {
+        return Ok(());
+    }
+    edit_disabled_file("replace_servers", |disabled| {
+        if servers.is_empty() {
+            if let Some(projects) = disabled["projects"].as_object_mut() {
+                projects.remove(working_dir);
+            }
+        } else {
+            disabled["projects"][working_dir] = Value::Object(servers.clone());
         }
-        if let Some(cmd) = s.command {
-            cfg["command"] = json!(cmd);
+        Ok(())
+    })
+    .await
+}
+
+// Servers of the local and user scopes are parked here while disabled.
+// Project-scoped servers stay in the committed .mcp.json: disabling one
+// rejects it through Claude Code's own approval lists instead.
+
+/// Where a scope's disabled servers are parked in the store
+fn scope_section<'a>(v: &'a Value, working_dir: &str, scope: ClaudeScope) -> Option<&'a Value> {
+    match scope {
+        ClaudeScope::Local => v.get("projects")?.get(working_dir),
+        ClaudeScope::Project => None,
+        ClaudeScope::User => v.get("user"),
+    }
+}
+
+fn scope_section_mut<'a>(
+    v: &'a mut Value,
+    working_dir: &str,
+    scope: ClaudeScope,
+) -> Result<&'a mut Value, String> {
+    let section = match scope {
+        ClaudeScope::Local => {
+            if !v["projects"].is_object() {
+                v["projects"] = json!({});
+            }
+            &mut v["projects"][working_dir]
         }
-        if let Some(args) = s.args {
-            cfg["args"] = json!(args);
+        ClaudeScope::Project => {
+            return Err("Project-scoped servers are not kept in the disabled store".to_string());
         }
-        if let Some(env) = s.env {
-            cfg["env"] = json!(env);
+        ClaudeScope::User => &mut v["user"],
+    };
+    if !section.is_object() {
+        *section = json!({});
+    }
+    Ok(section)
+}
+
+/// Disabled servers of one scope, or of every scope with the nearer one
+/// winning on a name clash
+fn disabled_in(v: &Value, working_dir: &str, scope: Option<ClaudeScope>) -> Result<Value, String> {
+    let mut merged = Map::new();
+    for current in ClaudeScope::ALL.into_iter().rev() {
+        if scope.is_some_and(|s| s != current) {
+            continue;
         }
-        disabled["projects"][&working_dir][&name] = cfg;
-        write_disabled_file(&disabled)?;
+        let servers = match current {
+            ClaudeScope::Project => claude_code_commands::rejected_mcpjson(working_dir)?,
+            _ => scope_section(v, working_dir, current)
+                .and_then(|s| s.as_object())
+                .cloned()
+                .unwrap_or_default(),
+        };
+        merged.extend(servers);
+    }
+    Ok(Value::Object(merged))
+}
 
-        // Remove from ~/.claude.json active list
-        let _ =
-            crate::claude_code_commands::claude_mcp_remove(name.clone(), working_dir.clone()).await;
+/// The scope a server is disabled in, nearest first
+fn disabled_scope(v: &Value, working_dir: &str, name: &str) -> Result<Option<ClaudeScope>, String> {
+    for scope in ClaudeScope::ALL {
+        let found = match scope {
+            ClaudeScope::Project => {
+                claude_code_commands::rejected_mcpjson(working_dir)?.contains_key(name)
+            }
+            _ => scope_section(v, working_dir, scope)
+                .and_then(|s| s.get(name))
+                .is_some(),
+        };
+        if found {
+            return Ok(Some(scope));
+        }
     }
-    Ok(disabled["projects"][&working_dir].clone())
+    Ok(None)
 }
 
 #[command]
-pub async fn claude_enable_server(working_dir: String, name: String) -> Result<Value, String> {
-    let mut disabled = read_disabled_file()?;
+pub async fn claude_list_disabled(
+    working_dir: String,
+    scope: Option<ClaudeScope>,
+) -> Result<Value, String> {
+    let v = read_disabled_file().await?;
+    disabled_in(&v, &working_dir, scope)
+}
 
+/// Disable a server: park it in the disabled store, or reject it when it
+/// comes from `.mcp.json`. Without a scope, the server Claude Code uses is
+/// disabled.
+#[command]
+pub async fn claude_disable_server(
+    working_dir: String,
+    name: String,
+    scope: Option<ClaudeScope>,
+) -> Result<Value, String> {
+    let scope = match scope {
+        Some(scope) => Some(scope),
+        None => claude_code_commands::effective_scope(&working_dir, &name)?,
+    };
+    let Some(scope) = scope else {
+        return already_disabled(&working_dir, &name).await;
+    };
+    if scope == ClaudeScope::Project {
+        claude_code_commands::set_approval(&working_dir, &name, McpjsonApproval::Rejected).await?;
+        return disabled_in(&read_disabled_file().await?, &working_dir, None);
+    }
+
+    // Read Claude config to fetch config for the named server
+    let Some(server_config) = claude_code_commands::read_scope(&working_dir, scope)?.remove(&name)
+    else {
+        return already_disabled(&working_dir, &name).await;
+    };
+
+    // Convert to JSON matching Manage shape
+    let server = claude_code_commands::parse_server_config(&name, &server_config)?;
+    let cfg = claude_code_commands::server_to_json(&server)?;
+
+    // Take it out of the scope first, so a failure can't leave it both active
+    // and disabled, and put it back if it can't be parked
+    claude_code_commands::claude_mcp_remove(name.clone(), working_dir.clone(), Some(scope)).await?;
+    let disabled = edit_disabled_file("disable_server", |disabled| {
+        scope_section_mut(disabled, &working_dir, scope)?[&name] = cfg.clone();
+        disabled_in(disabled, &working_dir, None)
+    })
+    .await;
+    if disabled.is_err()
+        && let Err(e) =
+            claude_code_commands::claude_mcp_add(server, working_dir.clone(), Some(scope)).await
+    {
+        println!(
+            "[ClaudeCode] Failed to restore {} after disabling: {}",
+            name, e
+        );
+    }
+    disabled
+}
+
+/// Disabling a server that is already disabled changes nothing; one that is
+/// neither active nor disabled is an error
+async fn already_disabled(working_dir: &str, name: &str) -> Result<Value, String> {
+    let v = read_disabled_file().await?;
+    if disabled_scope(&v, working_dir, name)?.is_none() {
+        return Err(format!("Server '{}' not found", name));
+    }
+    disabled_in(&v, working_dir, None)
+}
+
+/// Enable a server again in the scope it was disabled in
+#[command]
+pub async fn claude_enable_server(
+    working_dir: String,
+    name: String,
+    scope: Option<ClaudeScope>,
+) -> Result<Value, String> {
     // Read config from disabled store to re-add
-    let maybe_cfg = disabled
-        .get("projects")
-        .and_then(|p| p.get(&working_dir))
+    let v = read_disabled_file().await?;
+    let scope = match scope {
+        Some(scope) => Some(scope),
+        None => disabled_scope(&v, &working_dir, &name)?,
+    };
+    let Some(scope) = scope else {
+        // Enabling an active server changes nothing; an unknown one is an error
+        if claude_code_commands::effective_scope(&working_dir, &name)?.is_none() {
+            return Err(format!("Server '{}' not found", name));
+        }
+        return disabled_in(&v, &working_dir, None);
+    };
+    if scope == ClaudeScope::Project {
+        claude_code_commands::set_approval(&working_dir, &name, McpjsonApproval::Approved).await?;
+        return disabled_in(&v, &working_dir, None);
+    }
+
+    let maybe_cfg = scope_section(&v, &working_dir, scope)
         .and_then(|m| m.get(&name))
         .cloned();
 
     if let Some(cfg) = maybe_cfg {
         // Map disabled config back to ClaudeCodeServer and add
-        let server = crate::claude_code_commands::ClaudeCodeServer {
-            name: name.clone(),
-            r#type: cfg
-                .get("type")
-                .and_then(|v| v.as_str())
-                .unwrap_or("http")
-                .to_string(),
-            url: cfg
-                .get("url")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
-            command: cfg
-                .get("command")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
-            args: cfg.get("args").and_then(|v| v.as_array()).map(|arr| {
-                arr.iter()
-                    .filter_map(|x| x.as_str().map(|s| s.to_string()))
-                    .collect()
-            }),
-            env: cfg.get("env").and_then(|v| v.as_object()).map(|m| {
-                m.iter()
-                    .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
-                    .collect()
-            }),
-        };
-        let _ = crate::claude_code_commands::claude_mcp_add(server, working_dir.clone()).await;
+        let server = claude_code_commands::parse_server_config(&name, &cfg)?;
+        claude_code_commands::claude_mcp_add(server, working_dir.clone(), Some(scope)).await?;
     }
 
     // Remove from disabled store
-    if disabled["projects"].is_object() && disabled["projects"][&working_dir].is_object() {
-        if let Some(map) = disabled["projects"][&working_dir].as_object_mut() {
+    edit_disabled_file("enable_server", |disabled| {
+        if let Some(map) = scope_section_mut(disabled, &working_dir, scope)?.as_object_mut() {
             map.remove(&name);
         }
-    }
-    write_disabled_file(&disabled)?;
-
-    Ok(disabled
-        .get("projects")
-        .and_then(|p| p.get(&working_dir))
-        .cloned()
-        .unwrap_or(json!({})))
+        disabled_in(disabled, &working_dir, None)
+    })
+    .await
 }
 
+/// Change the config of a disabled server. A rejected `.mcp.json` server is
+/// edited in place and stays rejected.
 #[command]
 pub async fn claude_update_disabled(
     working_dir: String,
     name: String,
     server_config: Value,
+    scope: Option<ClaudeScope>,
 ) -> Result<Value, String> {
-    let mut disabled = read_disabled_file()?;
-    if !disabled["projects"].is_object() {
-        disabled["projects"] = json!({});
+    let scope = match scope {
+        Some(scope) => scope,
+        None => disabled_scope(&read_disabled_file().await?, &working_dir, &name)?
+            .unwrap_or(ClaudeScope::Local),
+    };
+    if scope == ClaudeScope::Project {
+        let server = claude_code_commands::parse_server_config(&name, &server_config)?;
+        claude_code_commands::claude_mcp_add(server, working_dir.clone(), Some(scope)).await?;
+        return disabled_in(&read_disabled_file().await?, &working_dir, None);
+    }
+
+    edit_disabled_file("update_disabled", |disabled| {
+        scope_section_mut(disabled, &working_dir, scope)?[&name] = server_config.clone();
+        disabled_in(disabled, &working_dir, None)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::sync::Mutex;
+
+    // Tests that break the disabled store on purpose keep the others out
+    static STORE: Mutex<()> = Mutex::const_new(());
+
+    async fn add(working_dir: &str, name: &str, command: &str, scope: ClaudeScope) {
+        let server =
+            claude_code_commands::parse_server_config(name, &json!({"command": command})).unwrap();
+        claude_code_commands::claude_mcp_add(server, working_dir.to_string(), Some(scope))
+            .await
+            .unwrap();
     }
-    if !disabled["projects"][&working_dir].is_object() {
-        disabled["projects"][&working_dir] = json!({});
+
+    fn project() -> (tempfile::TempDir, String) {
+        crate::test_home::init();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().display().to_string();
+        (dir, path)
+    }
+
+    #[tokio::test]
+    async fn disable_and_enable_in_every_scope() {
+        let _store = STORE.lock().await;
+        let (_project, dir) = project();
+        add(&dir, "toggle-local", "local", ClaudeScope::Local).await;
+        add(&dir, "toggle-project", "project", ClaudeScope::Project).await;
+        add(&dir, "toggle-user", "user", ClaudeScope::User).await;
+
+        for (name, scope) in [
+            ("toggle-local", ClaudeScope::Local),
+            ("toggle-project", ClaudeScope::Project),
+            ("toggle-user", ClaudeScope::User),
+        ] {
+            let disabled = claude_disable_server(dir.clone(), name.to_string(), None)
+                .await
+                .unwrap();
+            assert!(
+                disabled.get(name).is_some(),
+                "{} is listed as disabled",
+                name
+            );
+            assert_eq!(
+                claude_code_commands::effective_scope(&dir, name).unwrap(),
+                None
+            );
+            let listed = claude_list_disabled(dir.clone(), Some(scope))
+                .await
+                .unwrap();
+            assert!(
+                listed.get(name).is_some(),
+                "{} is disabled in its scope",
+                name
+            );
+
+            let disabled = claude_enable_server(dir.clone(), name.to_string(), None)
+                .await
+                .unwrap();
+            assert!(disabled.get(name).is_none());
+            assert_eq!(
+                claude_code_commands::effective_scope(&dir, name).unwrap(),
+                Some(scope)
+            );
+        }
+
+        // Enabling an active server changes nothing, an unknown one fails
+        assert!(
+            claude_enable_server(dir.clone(), "toggle-user".to_string(), None)
+                .await
+                .is_ok()
+        );
+        let err = claude_enable_server(dir.clone(), "missing".to_string(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Server 'missing' not found");
+        let err = claude_disable_server(dir.clone(), "missing".to_string(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Server 'missing' not found");
+
+        claude_code_commands::claude_mcp_remove(
+            "toggle-user".to_string(),
+            dir,
+            Some(ClaudeScope::User),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn disable_disables_the_server_in_use_only() {
+        let _store = STORE.lock().await;
+        let (_project, dir) = project();
+        add(&dir, "toggle-shadowed", "user", ClaudeScope::User).await;
+        add(&dir, "toggle-shadowed", "local", ClaudeScope::Local).await;
+
+        claude_disable_server(dir.clone(), "toggle-shadowed".to_string(), None)
+            .await
+            .unwrap();
+        let user = claude_code_commands::read_scope(&dir, ClaudeScope::User).unwrap();
+        assert_eq!(user["toggle-shadowed"]["command"], "user");
+        assert_eq!(
+            claude_code_commands::effective_scope(&dir, "toggle-shadowed").unwrap(),
+            Some(ClaudeScope::User)
+        );
+
+        claude_enable_server(dir.clone(), "toggle-shadowed".to_string(), None)
+            .await
+            .unwrap();
+        let local = claude_code_commands::read_scope(&dir, ClaudeScope::Local).unwrap();
+        assert_eq!(local["toggle-shadowed"]["command"], "local");
+
+        claude_code_commands::claude_mcp_remove(
+            "toggle-shadowed".to_string(),
+            dir,
+            Some(ClaudeScope::User),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_failed_disable_puts_the_server_back() {
+        let _store = STORE.lock().await;
+        let (_project, dir) = project();
+        add(&dir, "toggle-restored", "local", ClaudeScope::Local).await;
+
+        let path = get_disabled_path().unwrap();
+        let previous = std::fs::read_to_string(&path).ok();
+        std::fs::write(&path, "{ not json").unwrap();
+        let result = claude_disable_server(dir.clone(), "toggle-restored".to_string(), None).await;
+        match previous {
+            Some(previous) => std::fs::write(&path, previous).unwrap(),
+            None => std::fs::remove_file(&path).unwrap(),
+        }
+
+        assert!(result.is_err());
+        let local = claude_code_commands::read_scope(&dir, ClaudeScope::Local).unwrap();
+        assert_eq!(local["toggle-restored"]["command"], "local");
+        let disabled = claude_list_disabled(dir, None).await.unwrap();
+        assert!(disabled.get("toggle-restored").is_none());
     }
-    disabled["projects"][&working_dir][&name] = server_config;
-    write_disabled_file(&disabled)?;
-    Ok(disabled["projects"][&working_dir].clone())
 }
@@ -1,21 +1,40 @@
 use dirs::home_dir;
+use std::env;
 use std::path::{Path, PathBuf};
 
+use crate::client_descriptor::get_descriptor;
+
 pub struct ClientConfig {
     pub path: PathBuf,
 }
 
 impl ClientConfig {
-    pub fn new(name: &str, path: Option<&str>) -> Self {
-        let home = home_dir().expect("Failed to get home directory");
+    pub fn new(name: &str, path: Option<&str>) -> Result<Self, String> {
+        let resolved = match get_descriptor(name) {
+            Some(descriptor) => descriptor.resolve_path(path),
+            None => {
+                let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+                Self::builtin_path(name, path, &home)
+            }
+        };
 
+        match resolved {
+            Some(path) if !path.as_os_str().is_empty() => Ok(Self { path }),
+            _ => Err(format!(
+                "Cannot resolve config path for client '{}' on this platform; please provide a path",
+                name
+            )),
+        }
+    }
+
+    fn builtin_path(name: &str, path: Option<&str>, home: &Path) -> Option<PathBuf> {
         let path = match (name, path) {
-            ("claude", _) => Self::claude_config_path(&home),
-            ("cline", _) => Self::cline_config_path(&home),
+            ("claude", _) => return Self::claude_config_path(home),
+            ("cline", _) => return Self::cline_config_path(home),
             ("roo_code", Some(base_path)) if !base_path.is_empty() => {
                 PathBuf::from(base_path).join(".roo/mcp.json")
             }
-            ("roo_code", _) => Self::roo_config_path(&home),
+            ("roo_code", _) => return Self::roo_config_path(home),
             ("copilot", Some(base_path)) if !base_path.is_empty() => {
                 PathBuf::from(base_path).join(".vscode/mcp.json")
             }
@@ -36,41 +55,66 @@ impl ClientConfig {
                     given_path.join("mcp.json")
                 }
             }
-            _ => PathBuf::new(),
+            _ => return None,
         };
 
-        Self { path }
+        Some(path)
     }
 
-    fn claude_config_path(home: &Path) -> PathBuf {
+    fn claude_config_path(home: &Path) -> Option<PathBuf> {
         if cfg!(target_os = "macos") {
-            home.join("Library/Application Support/Claude/claude_desktop_config.json")
+            Some(home.join("Library/Application Support/Claude/claude_desktop_config.json"))
         } else if cfg!(target_os = "windows") {
-            home.join("AppData/Roaming/Claude/claude_desktop_config.json")
+            Some(home.join("AppData/Roaming/Claude/claude_desktop_config.json"))
+        } else if cfg!(target_os = "linux") {
+            Some(Self::xdg_config_dir(home).join("Claude/claude_desktop_config.json"))
         } else {
-            PathBuf::new()
+            None
         }
     }
 
-    fn vscode_global_storage_path(home: &Path, extension_id: &str, filename: &str) -> PathBuf {
-        let base_path = if cfg!(target_os = "macos") {
-            home.join("Library/Application Support/Code/User/globalStorage")
+    /// `$XDG_CONFIG_HOME`, falling back to `~/.config` when unset or not absolute
+    fn xdg_config_dir(home: &Path) -> PathBuf {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .unwrap_or_else(|| home.join(".config"))
+    }
+
+    /// VS Code's `User` directory, which holds settings and extension storage
+    pub(crate) fn vscode_user_dir(home: &Path) -> Option<PathBuf> {
+        if cfg!(target_os = "macos") {
+            Some(home.join("Library/Application Support/Code/User"))
         } else if cfg!(target_os = "windows") {
-            home.join("AppData/Roaming/Code/User/globalStorage")
+            Some(home.join("AppData/Roaming/Code/User"))
         } else if cfg!(target_os = "linux") {
-            home.join(".config/Code/User/globalStorage")
+            Some(Self::xdg_config_dir(home).join("Code/User"))
         } else {
-            return PathBuf::new();
-        };
+            None
+        }
+    }
+
+    fn vscode_global_storage_path(
+        home: &Path,
+        extension_id: &str,
+        filename: &str,
+    ) -> Option<PathBuf> {
+        let user_dir = Self::vscode_user_dir(home)?;
 
-        base_path.join(extension_id).join("settings").join(filename)
+        Some(
+            user_dir
+                .join("globalStorage")
+                .join(extension_id)
+                .join("settings")
+                .join(filename),
+        )
     }
 
-    fn cline_config_path(home: &Path) -> PathBuf {
+    fn cline_config_path(home: &Path) -> Option<PathBuf> {
         Self::vscode_global_storage_path(home, "saoudrizwan.claude-dev", "cline_mcp_settings.json")
     }
 
-    fn roo_config_path(home: &Path) -> PathBuf {
+    fn roo_config_path(home: &Path) -> Option<PathBuf> {
         Self::vscode_global_storage_path(home, "rooveterinaryinc.roo-cline", "mcp_settings.json")
     }
 
@@ -0,0 +1,255 @@
+use dirs::home_dir;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::backend::registry::is_builtin_client;
+use crate::backend::{DisabledStyle, Transport};
+use crate::json_manager::ConfigFormat;
+use crate::json_manager::utils::ClientLayout;
+
+// ~/.config/mcplinker/clients/acme.toml
+//
+// id = "acme"
+// format = "jsonc"
+// servers_key = "mcpServers"
+// disabled_style = "disabled_flag"
+// transports = ["stdio", "http"]
+// project_path = ".acme/mcp.json"
+//
+// [path]
+// macos = "~/Library/Application Support/Acme/mcp.json"
+// linux = "~/.config/acme/mcp.json"
+// windows = "~/AppData/Roaming/Acme/mcp.json"
+
+/// Config path of a user-defined client, per OS
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DescriptorPaths {
+    pub macos: Option<String>,
+    pub windows: Option<String>,
+    pub linux: Option<String>,
+    /// Used when there is no entry for the current OS
+    pub default: Option<String>,
+}
+
+/// A client declared in a descriptor file instead of being built in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientDescriptor {
+    /// Defaults to the file name without extension
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub path: DescriptorPaths,
+    /// Config path relative to the project root, used when a base path is given
+    #[serde(default)]
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub format: ConfigFormat,
+    #[serde(default = "default_servers_key")]
+    pub servers_key: String,
+    #[serde(default = "default_disabled_style")]
+    pub disabled_style: DisabledStyle,
+    #[serde(default = "default_transports")]
+    pub transports: Vec<Transport>,
+}
+
+fn default_servers_key() -> String {
+    "mcpServers".to_string()
+}
+
+fn default_disabled_style() -> DisabledStyle {
+    DisabledStyle::Section
+}
+
+fn default_transports() -> Vec<Transport> {
+    Transport::ALL.to_vec()
+}
+
+impl ClientDescriptor {
+    pub fn layout(&self) -> ClientLayout {
+        ClientLayout {
+            servers_key: self.servers_key.clone(),
+            disabled_style: self.disabled_style,
+            format: self.format,
+        }
+    }
+
+    /// Resolve the config path. A base path given by the caller wins: it is
+    /// the project to look in when `project_path` is set, and otherwise the
+    /// config file, or the directory holding it.
+    pub fn resolve_path(&self, base_path: Option<&str>) -> Option<PathBuf> {
+        let default = self.default_path();
+        let Some(base) = base_path.filter(|b| !b.is_empty()) else {
+            return default;
+        };
+
+        let base = expand_home(base);
+        let file_name = default.as_ref().and_then(|p| p.file_name());
+        Some(match (&self.project_path, file_name) {
+            (Some(project_path), _) => base.join(project_path),
+            (None, Some(file_name)) if base.is_dir() => base.join(file_name),
+            (None, _) => base,
+        })
+    }
+
+    /// The configured path for the current OS
+    fn default_path(&self) -> Option<PathBuf> {
+        let os_path = if cfg!(target_os = "macos") {
+            &self.path.macos
+        } else if cfg!(target_os = "windows") {
+            &self.path.windows
+        } else {
+            &self.path.linux
+        };
+        os_path
+            .as_ref()
+            .or(self.path.default.as_ref())
+            .map(|p| expand_home(p))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() {
+            return Err("missing `id`".to_string());
+        }
+        if is_builtin_client(&self.id) {
+            return Err(format!("`{}` is a built-in client", self.id));
+        }
+        if self.servers_key.is_empty() {
+            return Err("`servers_key` must not be empty".to_string());
+        }
+        if !matches!(
+            self.disabled_style,
+            DisabledStyle::Section | DisabledStyle::DisabledFlag | DisabledStyle::Unsupported
+        ) {
+            return Err(
+                "`disabled_style` must be one of section, disabled_flag, unsupported".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn get_descriptors_dir() -> Result<PathBuf, String> {
+    let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    Ok(home.join(".config/mcplinker/clients"))
+}
+
+/// Result of scanning the descriptor directory
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DescriptorScan {
+    pub clients: Vec<ClientDescriptor>,
+    /// One message per descriptor file that could not be loaded
+    pub errors: Vec<String>,
+}
+
+fn scan_descriptors() -> DescriptorScan {
+    let mut scan = DescriptorScan::default();
+    let dir = match get_descriptors_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            scan.errors.push(e);
+            return scan;
+        }
+    };
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        // No descriptor directory means no user-defined clients
+        Err(_) => return scan,
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        match load_descriptor(&path) {
+            Ok(descriptor) => {
+                if scan.clients.iter().any(|c| c.id == descriptor.id) {
+                    scan.errors.push(format!(
+                        "{}: duplicate client id `{}`",
+                        path.display(),
+                        descriptor.id
+                    ));
+                } else {
+                    scan.clients.push(descriptor);
+                }
+            }
+            Err(e) => scan.errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    for e in &scan.errors {
+        println!("[ClientDescriptor] {}", e);
+    }
+    scan
+}
+
+fn load_descriptor(path: &Path) -> Result<ClientDescriptor, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut descriptor: ClientDescriptor =
+        toml::from_str(&content).map_err(|e| format!("Failed to parse descriptor: {}", e))?;
+    if descriptor.id.is_empty()
+        && let Some(stem) = path.file_stem()
+    {
+        descriptor.id = stem.to_string_lossy().to_string();
+    }
+    descriptor.validate()?;
+    Ok(descriptor)
+}
+
+static DESCRIPTORS: Lazy<RwLock<HashMap<String, ClientDescriptor>>> =
+    Lazy::new(|| RwLock::new(index(scan_descriptors().clients)));
+
+fn index(clients: Vec<ClientDescriptor>) -> HashMap<String, ClientDescriptor> {
+    clients.into_iter().map(|c| (c.id.clone(), c)).collect()
+}
+
+/// Look up a user-defined client by id
+pub fn get_descriptor(client: &str) -> Option<ClientDescriptor> {
+    DESCRIPTORS
+        .read()
+        .ok()
+        .and_then(|map| map.get(client).cloned())
+}
+
+/// All user-defined clients, sorted by id
+pub fn all_descriptors() -> Vec<ClientDescriptor> {
+    let mut clients: Vec<ClientDescriptor> = DESCRIPTORS
+        .read()
+        .map(|map| map.values().cloned().collect())
+        .unwrap_or_default();
+    clients.sort_by(|a, b| a.id.cmp(&b.id));
+    clients
+}
+
+#[tauri::command]
+pub fn list_client_descriptors() -> Vec<ClientDescriptor> {
+    all_descriptors()
+}
+
+/// Rescan the descriptor directory, e.g. after the user added a file
+#[tauri::command]
+pub fn reload_client_descriptors() -> Result<DescriptorScan, String> {
+    let scan = scan_descriptors();
+    let mut map = DESCRIPTORS
+        .write()
+        .map_err(|_| "Client descriptor registry is poisoned".to_string())?;
+    *map = index(scan.clients.clone());
+    Ok(scan)
+}
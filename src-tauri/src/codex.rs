@@ -1,13 +1,16 @@
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
 use tokio::sync::Mutex;
-use toml_edit::{value, DocumentMut, InlineTable, Item, Table, Value};
+use toml_edit::{DocumentMut, InlineTable, Item, Table, Value, value};
 
-use crate::config::{get_config_path, CodexConfig};
+use crate::atomic_write;
+use crate::backup::{self, Operation};
+use crate::config::CodexConfig;
+use crate::config::codex::resolve_config_path;
+use crate::config_lock;
 
 fn default_enabled() -> bool {
     true
@@ -17,46 +20,108 @@ fn is_enabled_true(enabled: &bool) -> bool {
     *enabled
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Settings Codex takes on servers of either transport
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerOptions {
+    #[serde(default = "default_enabled", skip_serializing_if = "is_enabled_true")]
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup_timeout_sec: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_timeout_sec: Option<f64>,
+    /// Only these tools are exposed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled_tools: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled_tools: Option<Vec<String>>,
+    /// Keys not modelled here, written back as they were read
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum McpServerConfig {
     #[serde(rename = "stdio")]
     Stdio {
         command: String,
+        #[serde(default)]
         args: Vec<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         env: Option<HashMap<String, String>>,
-        #[serde(default = "default_enabled", skip_serializing_if = "is_enabled_true")]
-        enabled: bool,
+        /// Variables passed through from the environment Codex runs in
+        #[serde(skip_serializing_if = "Option::is_none")]
+        env_vars: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cwd: Option<String>,
+        #[serde(flatten)]
+        options: ServerOptions,
     },
     #[serde(rename = "http")]
     Http {
         url: String,
-        #[serde(default = "default_enabled", skip_serializing_if = "is_enabled_true")]
-        enabled: bool,
+        /// Variable holding the token sent as `Authorization: Bearer`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        bearer_token_env_var: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        http_headers: Option<HashMap<String, String>>,
+        /// Header name to the variable holding its value
+        #[serde(skip_serializing_if = "Option::is_none")]
+        env_http_headers: Option<HashMap<String, String>>,
+        #[serde(flatten)]
+        options: ServerOptions,
     },
 }
 
 impl McpServerConfig {
+    fn options(&self) -> &ServerOptions {
+        match self {
+            McpServerConfig::Stdio { options, .. } => options,
+            McpServerConfig::Http { options, .. } => options,
+        }
+    }
+
     fn is_enabled(&self) -> bool {
+        self.options().enabled
+    }
+
+    pub fn set_enabled(&mut self, flag: bool) {
         match self {
-            McpServerConfig::Stdio { enabled, .. } => *enabled,
-            McpServerConfig::Http { enabled, .. } => *enabled,
+            McpServerConfig::Stdio { options, .. } => options.enabled = flag,
+            McpServerConfig::Http { options, .. } => options.enabled = flag,
         }
     }
 }
 
-static CODEX_CFG_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+/// What an operation did to one server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Changed,
+    /// The server was already enabled, or disabled; nothing was written
+    AlreadyInState,
+    NotFound,
+}
 
-pub async fn read_mcp_servers() -> Result<HashMap<String, McpServerConfig>, String> {
-    let config_path = get_config_path()?;
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerOutcome {
+    pub name: String,
+    pub outcome: Outcome,
+}
+
+// Serializes edits within this process; the file lock covers other processes
+static CODEX_CFG_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
+pub async fn read_mcp_servers(
+    config_path: &Path,
+) -> Result<HashMap<String, McpServerConfig>, String> {
     if !config_path.exists() {
         println!("[Codex] config not found: {}", config_path.display());
         return Ok(HashMap::new());
     }
 
-    let content = fs::read_to_string(&config_path)
+    let content = fs::read_to_string(config_path)
         .await
         .map_err(|e| format!("Failed to read config file: {}", e))?;
 
@@ -76,8 +141,7 @@ pub async fn read_mcp_servers() -> Result<HashMap<String, McpServerConfig>, Stri
         .collect())
 }
 
-async fn load_config() -> Result<CodexConfig, String> {
-    let config_path = get_config_path()?;
+async fn load_config(config_path: &Path) -> Result<CodexConfig, String> {
     if !config_path.exists() {
         println!(
             "[Codex] load default config (no file): {}",
@@ -85,7 +149,7 @@ async fn load_config() -> Result<CodexConfig, String> {
         );
         return Ok(CodexConfig::default());
     }
-    let content = fs::read_to_string(&config_path)
+    let content = fs::read_to_string(config_path)
         .await
         .map_err(|e| format!("Failed to read config file: {}", e))?;
     let config: CodexConfig =
@@ -114,37 +178,28 @@ async fn load_document(config_path: &Path) -> Result<DocumentMut, String> {
     Ok(doc)
 }
 
-async fn persist_document(config_path: &Path, doc: DocumentMut) -> Result<(), String> {
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)
-            .await
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-    }
-
-    let tmp_path = config_path.with_extension("toml.tmp");
+async fn persist_document(
+    config_path: &Path,
+    doc: DocumentMut,
+    operation: &str,
+) -> Result<(), String> {
     let (active_count, disabled_count) = doc_counts(&doc);
     let toml_content = doc.to_string();
     println!(
-        "[Codex] save config: path={}, tmp={}, active={}, disabled={}",
+        "[Codex] save config: path={}, active={}, disabled={}",
         config_path.display(),
-        tmp_path.display(),
         active_count,
         disabled_count
     );
-    let mut file = fs::File::create(&tmp_path)
-        .await
-        .map_err(|e| format!("Failed to create temp file: {}", e))?;
-    file.write_all(toml_content.as_bytes())
-        .await
-        .map_err(|e| format!("Failed to write temp file: {}", e))?;
-    file.flush()
-        .await
-        .map_err(|e| format!("Failed to flush temp file: {}", e))?;
-    drop(file);
-    fs::rename(&tmp_path, config_path)
-        .await
-        .map_err(|e| format!("Failed to rename temp file: {}", e))?;
-    Ok(())
+    let (previous, _) = config_lock::read_with_fingerprint(config_path).await?;
+    backup::snapshot(
+        Operation::new("codex", operation),
+        config_path,
+        previous,
+        toml_content.clone(),
+    )
+    .await?;
+    atomic_write::write_file(config_path, toml_content.into_bytes()).await
 }
 
 fn ensure_table<'a>(doc: &'a mut DocumentMut, key: &str) -> Result<&'a mut Table, String> {
@@ -170,8 +225,9 @@ fn server_to_item(config: &McpServerConfig) -> Result<Item, String> {
         .map_err(|e| format!("Failed to parse serialized server config: {}", e))?
         .as_table_mut()
         .clone();
-    inline_child_table(&mut table, "env");
-    inline_child_table(&mut table, "headers");
+    for key in ["env", "http_headers", "env_http_headers"] {
+        inline_child_table(&mut table, key);
+    }
     Ok(Item::Table(table))
 }
 
@@ -296,40 +352,59 @@ fn inline_child_table(table: &mut Table, key: &str) {
     }
 }
 
-pub async fn add_mcp_server(name: String, config: McpServerConfig) -> Result<(), String> {
+pub async fn add_mcp_server(
+    config_path: &Path,
+    name: String,
+    config: McpServerConfig,
+) -> Result<(), String> {
     let _guard = CODEX_CFG_LOCK.lock().await;
-    let config_path = get_config_path()?;
-    let mut doc = load_document(&config_path).await?;
+    let _file_lock = config_lock::lock(config_path).await?;
+    let mut doc = load_document(config_path).await?;
     let table = ensure_table(&mut doc, "mcp_servers")?;
     let item = server_to_item(&config)?;
     table.insert(&name, item);
-    persist_document(&config_path, doc).await
+    persist_document(config_path, doc, "add_server").await
 }
 
-pub async fn delete_mcp_server(name: String) -> Result<(), String> {
+pub async fn delete_mcp_server(config_path: &Path, name: String) -> Result<Outcome, String> {
     println!("[Codex] delete request: {}", name);
+    let mut outcomes = batch_delete(config_path, vec![name]).await?;
+    Ok(outcomes.remove(0).outcome)
+}
+
+/// Delete servers in one read and one write of the config
+pub async fn batch_delete(
+    config_path: &Path,
+    names: Vec<String>,
+) -> Result<Vec<ServerOutcome>, String> {
     let _guard = CODEX_CFG_LOCK.lock().await;
-    let config_path = get_config_path()?;
-    let mut doc = load_document(&config_path).await?;
+    let _file_lock = config_lock::lock(config_path).await?;
+    let mut doc = load_document(config_path).await?;
     let (active_keys, disabled_keys) = partition_server_keys(&doc);
     println!(
         "[Codex] before delete: active_keys={:?} disabled_keys={:?}",
         active_keys, disabled_keys
     );
-    let mut removed = false;
-    if remove_entry(&mut doc, &name).is_some() {
-        removed = true;
-    }
-    if !removed {
-        return Err(format!("MCP server '{}' not found", name));
+    let outcomes: Vec<ServerOutcome> = names
+        .into_iter()
+        .map(|name| {
+            let outcome = match remove_entry(&mut doc, &name) {
+                Some(_) => Outcome::Changed,
+                None => Outcome::NotFound,
+            };
+            ServerOutcome { name, outcome }
+        })
+        .collect();
+    if outcomes.iter().any(|o| o.outcome == Outcome::Changed) {
+        println!("[Codex] delete matched, saving");
+        persist_document(config_path, doc, "remove_server").await?;
     }
-    println!("[Codex] delete matched, saving");
-    persist_document(&config_path, doc).await
+    Ok(outcomes)
 }
 
 // Disabled servers support for Codex
-pub async fn list_disabled() -> Result<HashMap<String, McpServerConfig>, String> {
-    let config = load_config().await?;
+pub async fn list_disabled(config_path: &Path) -> Result<HashMap<String, McpServerConfig>, String> {
+    let config = load_config(config_path).await?;
     let mut disabled: HashMap<String, McpServerConfig> = config
         .mcp_servers
         .into_iter()
@@ -339,48 +414,51 @@ pub async fn list_disabled() -> Result<HashMap<String, McpServerConfig>, String>
     Ok(disabled)
 }
 
-pub async fn disable(name: &str) -> Result<(), String> {
+async fn set_enabled(config_path: &Path, name: &str, enabled: bool) -> Result<Outcome, String> {
     let _guard = CODEX_CFG_LOCK.lock().await;
-    let config_path = get_config_path()?;
-    let mut doc = load_document(&config_path).await?;
+    let _file_lock = config_lock::lock(config_path).await?;
+    let mut doc = load_document(config_path).await?;
+    let action = if enabled { "enable" } else { "disable" };
     println!(
-        "[Codex] disable request: {} | active_keys={:?}",
+        "[Codex] {} request: {} | active_keys={:?}",
+        action,
         name,
         partition_server_keys(&doc).0
     );
-    if let Some(server_table) = get_server_table_mut(&mut doc, name) {
-        set_enabled_field(server_table, false);
-        println!("[Codex] disable matched, saving");
-        persist_document(&config_path, doc).await
-    } else {
-        println!("[Codex] disable miss");
-        Ok(())
+    let Some(server_table) = get_server_table_mut(&mut doc, name) else {
+        println!("[Codex] {} miss: {}", action, name);
+        return Ok(Outcome::NotFound);
+    };
+    let current = server_table
+        .get("enabled")
+        .and_then(Item::as_value)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true);
+    if current == enabled {
+        return Ok(Outcome::AlreadyInState);
     }
+    set_enabled_field(server_table, enabled);
+    println!("[Codex] {} matched, saving", action);
+    persist_document(config_path, doc, &format!("{}_server", action)).await?;
+    Ok(Outcome::Changed)
 }
 
-pub async fn enable(name: &str) -> Result<(), String> {
-    let _guard = CODEX_CFG_LOCK.lock().await;
-    let config_path = get_config_path()?;
-    let mut doc = load_document(&config_path).await?;
-    println!(
-        "[Codex] enable request: {} | disabled_keys={:?}",
-        name,
-        partition_server_keys(&doc).1
-    );
-    if let Some(server_table) = get_server_table_mut(&mut doc, name) {
-        set_enabled_field(server_table, true);
-        println!("[Codex] enable matched, saving");
-        persist_document(&config_path, doc).await
-    } else {
-        println!("[Codex] enable miss");
-        Ok(())
-    }
+pub async fn disable(config_path: &Path, name: &str) -> Result<Outcome, String> {
+    set_enabled(config_path, name, false).await
+}
+
+pub async fn enable(config_path: &Path, name: &str) -> Result<Outcome, String> {
+    set_enabled(config_path, name, true).await
 }
 
-pub async fn update_disabled(name: &str, server: McpServerConfig) -> Result<(), String> {
+pub async fn update_disabled(
+    config_path: &Path,
+    name: &str,
+    server: McpServerConfig,
+) -> Result<(), String> {
     let _guard = CODEX_CFG_LOCK.lock().await;
-    let config_path = get_config_path()?;
-    let mut doc = load_document(&config_path).await?;
+    let _file_lock = config_lock::lock(config_path).await?;
+    let mut doc = load_document(config_path).await?;
     println!(
         "[Codex] update_disabled: {} | disabled_keys(before)={:?}",
         name,
@@ -394,5 +472,235 @@ pub async fn update_disabled(name: &str, server: McpServerConfig) -> Result<(),
         "[Codex] update_disabled saved | disabled_keys(after)={:?}",
         partition_server_keys(&doc).1
     );
-    persist_document(&config_path, doc).await
+    persist_document(config_path, doc, "update_disabled").await
+}
+
+/// Replace the whole `mcp_servers` table in one write. Entries whose config is
+/// unchanged keep their original formatting.
+pub async fn replace_servers(
+    config_path: &Path,
+    servers: HashMap<String, McpServerConfig>,
+) -> Result<(), String> {
+    let _guard = CODEX_CFG_LOCK.lock().await;
+    let _file_lock = config_lock::lock(config_path).await?;
+    let mut doc = load_document(config_path).await?;
+    let current: CodexConfig = toml::from_str(&doc.to_string())
+        .map_err(|e| format!("Failed to parse config file: {}", e))?;
+    let table = ensure_table(&mut doc, "mcp_servers")?;
+    let stale: Vec<String> = table
+        .iter()
+        .map(|(k, _)| k.to_string())
+        .filter(|k| !servers.contains_key(k))
+        .collect();
+    for name in &stale {
+        table.remove(name);
+    }
+    for (name, config) in servers {
+        if current.mcp_servers.get(&name) == Some(&config) {
+            continue;
+        }
+        table.insert(&name, server_to_item(&config)?);
+    }
+    println!(
+        "[Codex] replace servers: removed={:?} | active_keys={:?}",
+        stale,
+        partition_server_keys(&doc).0
+    );
+    persist_document(config_path, doc, "replace_servers").await
+}
+
+/// A profile of a Codex home, as `codex --profile <name>` applies it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexProfile {
+    pub name: String,
+    /// Used when Codex starts without `--profile`
+    pub is_default: bool,
+    /// Top-level settings the profile overrides, such as `model`
+    pub overrides: Vec<String>,
+    /// The profile's values for them
+    pub settings: serde_json::Value,
+}
+
+/// Profiles of a Codex home and the settings each overrides. Profiles can't
+/// override `mcp_servers`, so every profile runs the same servers and they
+/// are managed for the whole home.
+#[tauri::command]
+pub async fn list_codex_profiles(path: Option<String>) -> Result<Vec<CodexProfile>, String> {
+    let config_path = resolve_config_path(path.as_deref())?;
+    let config = load_config(&config_path).await?;
+
+    let mut profiles: Vec<CodexProfile> = config
+        .profiles
+        .into_iter()
+        .map(|(name, settings)| {
+            let mut overrides: Vec<String> = settings
+                .as_object()
+                .map(|table| table.keys().cloned().collect())
+                .unwrap_or_default();
+            overrides.sort();
+            CodexProfile {
+                is_default: config.profile.as_deref() == Some(name.as_str()),
+                name,
+                overrides,
+                settings,
+            }
+        })
+        .collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CONFIG: &str = r#"model = "o3"
+
+# Search, pinned to the internal mirror
+[mcp_servers.search]
+type = "stdio"
+command = "npx"
+args = ["-y", "search-mcp"]
+env_vars = ["SEARCH_TOKEN"]
+cwd = "/srv/search"
+startup_timeout_sec = 20.0
+enabled_tools = ["query"]
+# Read by a newer Codex
+experimental_resume = true
+meta = { owner = "infra" }
+
+[mcp_servers.docs]
+type = "http"
+url = "https://docs.example.com/mcp"
+bearer_token_env_var = "DOCS_TOKEN"
+"#;
+
+    fn setup() -> (tempfile::TempDir, std::path::PathBuf) {
+        crate::test_home::init();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, CONFIG).unwrap();
+        (dir, path)
+    }
+
+    fn search_section(path: &Path) -> String {
+        let content = std::fs::read_to_string(path).unwrap();
+        let start = content.find("# Search").unwrap();
+        let end = content.find("[mcp_servers.docs]").unwrap_or(content.len());
+        content[start..end].to_string()
+    }
+
+    fn original_search_section() -> &'static str {
+        let start = CONFIG.find("# Search").unwrap();
+        &CONFIG[start..CONFIG.find("[mcp_servers.docs]").unwrap()]
+    }
+
+    #[tokio::test]
+    async fn reads_typed_fields_and_keeps_unknown_keys() {
+        let (_dir, path) = setup();
+        let servers = read_mcp_servers(&path).await.unwrap();
+        let McpServerConfig::Stdio {
+            env_vars,
+            cwd,
+            options,
+            ..
+        } = &servers["search"]
+        else {
+            panic!("search is a stdio server");
+        };
+        assert_eq!(env_vars.as_deref(), Some(&["SEARCH_TOKEN".to_string()][..]));
+        assert_eq!(cwd.as_deref(), Some("/srv/search"));
+        assert_eq!(options.startup_timeout_sec, Some(20.0));
+        assert_eq!(options.enabled_tools, Some(vec!["query".to_string()]));
+        assert_eq!(options.extra["experimental_resume"], json!(true));
+        assert_eq!(options.extra["meta"], json!({"owner": "infra"}));
+        assert!(!options.extra.contains_key("type"));
+    }
+
+    #[tokio::test]
+    async fn add_leaves_other_servers_as_written() {
+        let (_dir, path) = setup();
+        let config = read_mcp_servers(&path).await.unwrap()["docs"].clone();
+        add_mcp_server(&path, "docs2".to_string(), config)
+            .await
+            .unwrap();
+
+        assert_eq!(search_section(&path), original_search_section());
+        let servers = read_mcp_servers(&path).await.unwrap();
+        assert_eq!(servers["docs2"], servers["docs"]);
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("model = \"o3\""));
+    }
+
+    #[tokio::test]
+    async fn replace_keeps_unchanged_entries_and_unknown_keys_of_changed_ones() {
+        let (_dir, path) = setup();
+        let mut servers = read_mcp_servers(&path).await.unwrap();
+
+        // Unchanged, the entry keeps its comments and layout
+        replace_servers(&path, servers.clone()).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), CONFIG);
+
+        // Rewritten, it keeps every key it had
+        if let Some(McpServerConfig::Stdio { args, .. }) = servers.get_mut("search") {
+            args.push("--verbose".to_string());
+        }
+        replace_servers(&path, servers.clone()).await.unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("experimental_resume = true"));
+        assert!(content.contains("env_vars = [\"SEARCH_TOKEN\"]"));
+        assert_eq!(read_mcp_servers(&path).await.unwrap(), servers);
+    }
+
+    #[tokio::test]
+    async fn disable_and_enable_touch_only_the_flag() {
+        let (_dir, path) = setup();
+        let before = read_mcp_servers(&path).await.unwrap()["search"].clone();
+
+        assert_eq!(disable(&path, "search").await.unwrap(), Outcome::Changed);
+        let section = search_section(&path);
+        assert!(section.contains("# Read by a newer Codex"));
+        assert!(section.contains("enabled = false"));
+        let mut disabled = list_disabled(&path).await.unwrap()["search"].clone();
+        disabled.set_enabled(true);
+        assert_eq!(disabled, before);
+
+        assert_eq!(enable(&path, "search").await.unwrap(), Outcome::Changed);
+        assert_eq!(search_section(&path), original_search_section());
+        assert_eq!(read_mcp_servers(&path).await.unwrap()["search"], before);
+    }
+
+    #[tokio::test]
+    async fn lists_profiles_with_their_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let config = format!(
+            "profile = \"fast\"\n{}\n[profiles.fast]\nmodel = \"o4-mini\"\napproval_policy = \"never\"\n\n\
+             [profiles.deep]\nmodel = \"o3\"\n",
+            CONFIG
+        );
+        std::fs::write(&path, config).unwrap();
+
+        // A Codex home directory stands for its config.toml
+        let profiles = list_codex_profiles(Some(dir.path().display().to_string()))
+            .await
+            .unwrap();
+        let summary: Vec<(&str, bool, Vec<&str>)> = profiles
+            .iter()
+            .map(|p| {
+                let keys = p.overrides.iter().map(String::as_str).collect();
+                (p.name.as_str(), p.is_default, keys)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("deep", false, vec!["model"]),
+                ("fast", true, vec!["approval_policy", "model"])
+            ]
+        );
+        assert_eq!(profiles[1].settings["model"], "o4-mini");
+    }
 }
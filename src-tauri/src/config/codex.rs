@@ -1,8 +1,10 @@
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::env;
 use std::path::PathBuf;
 
+use crate::client_descriptor::expand_home;
 // Reuse the McpServerConfig definition from crate::codex
 use crate::codex::McpServerConfig;
 
@@ -18,9 +20,99 @@ pub struct CodexConfig {
     pub model_providers: HashMap<String, serde_json::Value>,
     #[serde(default)]
     pub profiles: HashMap<String, serde_json::Value>,
+    /// The profile Codex uses when none is given
+    #[serde(default)]
+    pub profile: Option<String>,
 }
 
-pub fn get_config_path() -> Result<PathBuf, String> {
+/// The Codex home: `$CODEX_HOME` when set, as Codex itself does, `~/.codex` otherwise
+pub fn get_codex_home() -> Result<PathBuf, String> {
+    if let Some(dir) = env::var_os("CODEX_HOME").filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
     let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
-    Ok(home.join(".codex").join("config.toml"))
+    Ok(home.join(".codex"))
+}
+
+pub fn get_config_path() -> Result<PathBuf, String> {
+    Ok(get_codex_home()?.join("config.toml"))
+}
+
+/// The config of the Codex home a caller picked: a home directory or its
+/// `config.toml`. Without one, the default home's.
+pub fn resolve_config_path(path: Option<&str>) -> Result<PathBuf, String> {
+    match path.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(p) if p.ends_with(".toml") => Ok(expand_home(p)),
+        Some(p) => Ok(expand_home(p).join("config.toml")),
+        None => get_config_path(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    // CODEX_HOME is process-wide, so the tests that set it take turns
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn with_codex_home<T>(value: Option<&Path>, f: impl FnOnce() -> T) -> T {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = env::var_os("CODEX_HOME");
+        // SAFETY: every test that touches CODEX_HOME holds ENV_LOCK
+        unsafe {
+            match value {
+                Some(value) => env::set_var("CODEX_HOME", value),
+                None => env::remove_var("CODEX_HOME"),
+            }
+        }
+        let result = f();
+        unsafe {
+            match previous {
+                Some(previous) => env::set_var("CODEX_HOME", previous),
+                None => env::remove_var("CODEX_HOME"),
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn follows_codex_home() {
+        let home = crate::test_home::init();
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config.toml");
+
+        with_codex_home(Some(dir.path()), || {
+            assert_eq!(get_config_path().unwrap(), config);
+            assert_eq!(resolve_config_path(None).unwrap(), config);
+            assert_eq!(resolve_config_path(Some("  ")).unwrap(), config);
+        });
+
+        let default = home.join(".codex/config.toml");
+        with_codex_home(None, || assert_eq!(get_config_path().unwrap(), default));
+        with_codex_home(Some(Path::new("")), || {
+            assert_eq!(get_config_path().unwrap(), default)
+        });
+    }
+
+    #[test]
+    fn a_picked_home_or_file_wins_over_codex_home() {
+        let home = crate::test_home::init();
+        let (dir, team) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let team_path = team.path().display().to_string();
+
+        with_codex_home(Some(dir.path()), || {
+            assert_eq!(
+                resolve_config_path(Some(&team_path)).unwrap(),
+                team.path().join("config.toml")
+            );
+            let file = team.path().join("ci.toml");
+            assert_eq!(resolve_config_path(file.to_str()).unwrap(), file);
+            assert_eq!(
+                resolve_config_path(Some("~/teams/infra")).unwrap(),
+                home.join("teams/infra/config.toml")
+            );
+        });
+    }
 }
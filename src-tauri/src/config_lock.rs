@@ -0,0 +1,226 @@
+use dirs::home_dir;
+use std::fs::{self, File, TryLockError};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::task;
+
+// Two guards around every read-modify-write of a client config:
+//
+// - An advisory lock per config file, held in ~/.config/mcplinker/locks, so
+//   mcp-linker windows and processes take turns.
+// - A fingerprint of the file taken when it is read and checked again right
+//   before the new version is renamed into place. Claude Code, Cursor and
+//   friends don't know about our lock, so this is how we notice that one of
+//   them rewrote the file in between.
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_POLL: Duration = Duration::from_millis(50);
+
+/// How many times an edit is replayed on a fresh read after a conflict
+pub const MAX_EDIT_ATTEMPTS: usize = 3;
+
+/// Exclusive lock on one config file, released on drop
+pub struct ConfigLock {
+    _file: File,
+}
+
+/// Take the lock for `path`, waiting up to a few seconds for other holders
+pub fn lock_blocking(path: &Path) -> Result<ConfigLock, String> {
+    let lock_path = lock_path_for(path)?;
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create lock directory: {}", e))?;
+    }
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| format!("Failed to open lock file: {}", e))?;
+
+    let started = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(ConfigLock { _file: file }),
+            Err(TryLockError::WouldBlock) if started.elapsed() < LOCK_TIMEOUT => {
+                thread::sleep(LOCK_POLL)
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(format!(
+                    "Timed out waiting for another mcp-linker operation on {}",
+                    path.display()
+                ));
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(format!("Failed to lock {}: {}", path.display(), e));
+            }
+        }
+    }
+}
+
+/// Async wrapper around [`lock_blocking`]
+pub async fn lock(path: &Path) -> Result<ConfigLock, String> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || lock_blocking(&path))
+        .await
+        .map_err(|e| format!("Failed to run blocking task for file lock: {}", e))?
+}
+
+fn lock_path_for(path: &Path) -> Result<PathBuf, String> {
+    let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "config".to_string());
+    // The same file reached through a symlink must share the lock, whether or
+    // not it exists yet
+    let target = if path.is_symlink() {
+        fs::canonicalize(path).ok()
+    } else {
+        path.parent()
+            .and_then(|dir| fs::canonicalize(dir).ok())
+            .map(|dir| dir.join(&name))
+    }
+    .unwrap_or_else(|| path.to_path_buf());
+    let key = fnv1a(target.to_string_lossy().as_bytes());
+    Ok(home
+        .join(".config/mcplinker/locks")
+        .join(format!("{}-{:016x}.lock", name, key)))
+}
+
+/// Stable across builds, unlike `DefaultHasher`
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// What a config file looked like when it was read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    state: Option<FileState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileState {
+    len: u64,
+    modified: Option<SystemTime>,
+    hash: u64,
+}
+
+impl Fingerprint {
+    /// Fingerprint of a file that does not exist
+    pub fn missing() -> Self {
+        Self { state: None }
+    }
+
+    /// Whether the file on disk is still the one that was read
+    pub fn matches_disk(&self, path: &Path) -> Result<bool, String> {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(self.state.is_none()),
+            Err(e) => return Err(format!("Failed to read file metadata: {}", e)),
+        };
+        let Some(state) = &self.state else {
+            return Ok(false);
+        };
+        if state.len != metadata.len() {
+            return Ok(false);
+        }
+        if state.modified.is_some() && state.modified == metadata.modified().ok() {
+            return Ok(true);
+        }
+        // Touched but maybe not changed: compare the contents
+        let contents = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        Ok(fnv1a(&contents) == state.hash)
+    }
+}
+
+/// Read a file and fingerprint it; `None` contents when it does not exist
+pub fn read_with_fingerprint_blocking(
+    path: &Path,
+) -> Result<(Option<String>, Fingerprint), String> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok((None, Fingerprint::missing())),
+        Err(e) => return Err(format!("Failed to read file metadata: {}", e)),
+    };
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok((None, Fingerprint::missing())),
+        Err(e) => return Err(format!("Failed to read file: {}", e)),
+    };
+    let fingerprint = Fingerprint {
+        state: Some(FileState {
+            len: contents.len() as u64,
+            modified: metadata.modified().ok(),
+            hash: fnv1a(contents.as_bytes()),
+        }),
+    };
+    Ok((Some(contents), fingerprint))
+}
+
+/// Async wrapper around [`read_with_fingerprint_blocking`]
+pub async fn read_with_fingerprint(path: &Path) -> Result<(Option<String>, Fingerprint), String> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || read_with_fingerprint_blocking(&path))
+        .await
+        .map_err(|e| format!("Failed to run blocking task for file read: {}", e))?
+}
+
+pub fn conflict_error(path: &Path) -> String {
+    format!(
+        "{} was changed by another program while mcp-linker was editing it; please try again",
+        path.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_the_reference_values() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn fingerprint_notices_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+
+        let (contents, missing) = read_with_fingerprint_blocking(&path).unwrap();
+        assert_eq!(contents, None);
+        assert!(missing.matches_disk(&path).unwrap());
+
+        fs::write(&path, "{}").unwrap();
+        assert!(!missing.matches_disk(&path).unwrap());
+        let (contents, fingerprint) = read_with_fingerprint_blocking(&path).unwrap();
+        assert_eq!(contents.as_deref(), Some("{}"));
+        assert!(fingerprint.matches_disk(&path).unwrap());
+
+        // Same length, other contents
+        fs::write(&path, "[]").unwrap();
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert!(!fingerprint.matches_disk(&path).unwrap());
+
+        fs::remove_file(&path).unwrap();
+        assert!(!fingerprint.matches_disk(&path).unwrap());
+    }
+
+    #[test]
+    fn rewriting_the_same_contents_still_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{}").unwrap();
+        let (_, fingerprint) = read_with_fingerprint_blocking(&path).unwrap();
+
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert!(fingerprint.matches_disk(&path).unwrap());
+    }
+}
@@ -0,0 +1,200 @@
+use dirs::home_dir;
+use serde::Serialize;
+use serde_json::Value;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::backend::get_backend;
+use crate::backend::registry::builtin_clients;
+use crate::client::ClientConfig;
+use crate::client_descriptor::all_descriptors;
+use crate::config::get_config_path;
+use crate::json_manager::JsonManager;
+
+/// What was found for one client on this machine
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedClient {
+    pub client: String,
+    pub installed: bool,
+    pub config_path: Option<String>,
+    pub config_exists: bool,
+    /// `None` when there is no config file to parse
+    pub config_valid: Option<bool>,
+    /// Active and disabled servers in the config
+    pub server_count: Option<usize>,
+    pub error: Option<String>,
+    /// Signals that the client is installed
+    pub evidence: Vec<String>,
+    /// Why the client was judged absent
+    pub reason: Option<String>,
+}
+
+/// Places that show a client is installed, besides its config file
+struct Probe {
+    dirs: Vec<PathBuf>,
+    executables: &'static [&'static str],
+}
+
+fn probe_for(client: &str, home: &Path) -> Probe {
+    let vscode_storage = ClientConfig::vscode_user_dir(home).map(|d| d.join("globalStorage"));
+    let (dirs, executables): (Vec<PathBuf>, &'static [&'static str]) = match client {
+        "claude" => (
+            ClientConfig::new("claude", None)
+                .ok()
+                .and_then(|cfg| cfg.get_path().parent().map(Path::to_path_buf))
+                .map(|p| vec![p])
+                .unwrap_or_default(),
+            &["claude-desktop"],
+        ),
+        "claude_code" => (vec![home.join(".claude")], &["claude"]),
+        "cline" => (
+            vscode_storage
+                .map(|d| vec![d.join("saoudrizwan.claude-dev")])
+                .unwrap_or_default(),
+            &[],
+        ),
+        "roo_code" => (
+            vscode_storage
+                .map(|d| vec![d.join("rooveterinaryinc.roo-cline")])
+                .unwrap_or_default(),
+            &[],
+        ),
+        "copilot" => (
+            ClientConfig::vscode_user_dir(home)
+                .map(|d| vec![d])
+                .unwrap_or_default(),
+            &["code"],
+        ),
+        "cursor" => (vec![home.join(".cursor")], &["cursor"]),
+        "windsurf" => (vec![home.join(".codeium/windsurf")], &["windsurf"]),
+        "codex" => (
+            get_config_path()
+                .ok()
+                .and_then(|p| p.parent().map(Path::to_path_buf))
+                .map(|p| vec![p])
+                .unwrap_or_default(),
+            &["codex"],
+        ),
+        "mcphub" => (vec![home.join(".config/mcphub")], &["mcphub"]),
+        _ => (Vec::new(), &[]),
+    };
+    Probe { dirs, executables }
+}
+
+/// Find an executable on PATH without spawning a shell
+fn find_executable(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    let extensions: &[&str] = if cfg!(target_os = "windows") {
+        &["exe", "cmd", "bat"]
+    } else {
+        &[""]
+    };
+    env::split_paths(&paths).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let candidate = if ext.is_empty() {
+                dir.join(name)
+            } else {
+                dir.join(format!("{}.{}", name, ext))
+            };
+            candidate.is_file().then_some(candidate)
+        })
+    })
+}
+
+fn config_path_for(client: &str) -> Option<PathBuf> {
+    match client {
+        "codex" => get_config_path().ok(),
+        "claude_code" => home_dir().map(|home| home.join(".claude.json")),
+        _ => ClientConfig::new(client, None)
+            .ok()
+            .map(|cfg| cfg.get_path().to_path_buf()),
+    }
+}
+
+/// Count servers in `~/.claude.json`, which spans every project plus the user scope
+fn count_claude_code_servers(config: &Value) -> usize {
+    let count = |v: Option<&Value>| v.and_then(Value::as_object).map_or(0, |m| m.len());
+    let projects = config
+        .get("projects")
+        .and_then(Value::as_object)
+        .map_or(0, |projects| {
+            projects.values().map(|p| count(p.get("mcpServers"))).sum()
+        });
+    count(config.get("mcpServers")) + projects
+}
+
+async fn count_servers(client: &str, path: &Path) -> Result<usize, String> {
+    if client == "claude_code" {
+        let config = JsonManager::read_json_file(path).await?;
+        return Ok(count_claude_code_servers(&config));
+    }
+    let servers = get_backend(client, None)?.read_servers().await?;
+    Ok(servers.active.len() + servers.disabled.len())
+}
+
+async fn detect_client(client: &str, home: &Path) -> DetectedClient {
+    let config_path = config_path_for(client);
+    let config_exists = config_path.as_ref().is_some_and(|p| p.is_file());
+    let mut evidence = Vec::new();
+    let mut absent = Vec::new();
+
+    match &config_path {
+        Some(p) if config_exists => evidence.push(format!("config file {}", p.display())),
+        Some(p) => absent.push(format!("no config file at {}", p.display())),
+        None => absent.push("no config path for this platform".to_string()),
+    }
+
+    let probe = probe_for(client, home);
+    for dir in &probe.dirs {
+        if dir.is_dir() {
+            evidence.push(format!("directory {}", dir.display()));
+        } else {
+            absent.push(format!("no directory {}", dir.display()));
+        }
+    }
+    for exe in probe.executables {
+        match find_executable(exe) {
+            Some(p) => evidence.push(format!("executable {}", p.display())),
+            None => absent.push(format!("no `{}` on PATH", exe)),
+        }
+    }
+
+    // mcp-linker's own config is always available
+    let installed = client == "mcplinker" || !evidence.is_empty();
+
+    let (config_valid, server_count, error) = match &config_path {
+        Some(p) if config_exists => match count_servers(client, p).await {
+            Ok(count) => (Some(true), Some(count), None),
+            Err(e) => (Some(false), None, Some(e)),
+        },
+        _ => (None, None, None),
+    };
+
+    DetectedClient {
+        client: client.to_string(),
+        installed,
+        config_path: config_path.map(|p| p.to_string_lossy().to_string()),
+        config_exists,
+        config_valid,
+        server_count,
+        error,
+        evidence,
+        reason: (!installed).then(|| absent.join("; ")),
+    }
+}
+
+/// Probe every built-in and user-defined client and report which are installed
+#[tauri::command]
+pub async fn detect_clients() -> Result<Vec<DetectedClient>, String> {
+    let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+
+    let mut clients: Vec<String> = builtin_clients().into_iter().map(String::from).collect();
+    clients.extend(all_descriptors().into_iter().map(|d| d.id));
+
+    let mut report = Vec::with_capacity(clients.len());
+    for client in &clients {
+        report.push(detect_client(client, &home).await);
+    }
+    Ok(report)
+}
@@ -1,9 +1,26 @@
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::io::ErrorKind;
 use std::path::Path;
 use tokio::fs;
 use tokio::task;
 
+use super::json_edit::edit_json_text;
+use super::jsonc::strip_jsonc;
+use crate::backup::{self, Operation};
+use crate::{atomic_write, config_lock};
+
+/// File format of a client config
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    #[default]
+    Json,
+    Jsonc,
+    Toml,
+    Yaml,
+}
+
 /// Read JSON file asynchronously
 pub async fn read_json_file(path: &Path) -> Result<Value, String> {
     let path_buf = path.to_path_buf();
@@ -25,32 +42,113 @@ pub async fn read_json_file(path: &Path) -> Result<Value, String> {
     }
 }
 
-/// Write JSON file asynchronously
+/// Write JSON file asynchronously, keeping the formatting of an existing file
 pub async fn write_json_file(path: &Path, content: &Value) -> Result<(), String> {
-    let path_buf = path.to_path_buf();
-    let content_cloned = content.clone(); // Clone for the blocking task
-
-    // Ensure directory exists
-    if let Some(parent) = path_buf.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)
-                .await
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
+    let operation = Operation::new("other", "write_file");
+    write_config_file(path, ConfigFormat::Json, &operation, content).await
+}
+
+/// Read a config file in any supported format into a JSON value
+pub async fn read_config_file(path: &Path, format: ConfigFormat) -> Result<Value, String> {
+    let content = match fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(json!({})),
+        Err(e) => return Err(format!("Failed to read file: {}", e)),
+    };
+    parse_config(&content, format)
+}
+
+fn parse_config(content: &str, format: ConfigFormat) -> Result<Value, String> {
+    let parsed: Result<Value, String> = match format {
+        ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        ConfigFormat::Jsonc => {
+            serde_json::from_str(&strip_jsonc(content)).map_err(|e| e.to_string())
         }
+        ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+        ConfigFormat::Yaml => serde_norway::from_str(content).map_err(|e| e.to_string()),
+    };
+    let value = parsed.map_err(|e| format!("Failed to parse {:?} config: {}", format, e))?;
+
+    // An empty YAML document parses as null
+    if value.is_null() {
+        Ok(json!({}))
+    } else {
+        Ok(value)
     }
+}
 
-    // Serialize JSON in a blocking task
-    let json_string_result = task::spawn_blocking(move || {
-        serde_json::to_string_pretty(&content_cloned)
-            .map_err(|e| format!("Failed to serialize JSON: {}", e))
-    })
-    .await
-    .map_err(|e| format!("Failed to run blocking task for JSON serialization: {}", e))?;
+/// Serialize a config, editing `original` in place for JSON formats
+fn render_config(
+    original: Option<&str>,
+    format: ConfigFormat,
+    content: &Value,
+) -> Result<String, String> {
+    match format {
+        ConfigFormat::Json | ConfigFormat::Jsonc => edit_json_text(original, content),
+        ConfigFormat::Toml => {
+            toml::to_string_pretty(content).map_err(|e| format!("Failed to serialize TOML: {}", e))
+        }
+        ConfigFormat::Yaml => {
+            serde_norway::to_string(content).map_err(|e| format!("Failed to serialize YAML: {}", e))
+        }
+    }
+}
+
+/// Write a JSON value to a config file in the given format, backing up the old file
+pub async fn write_config_file(
+    path: &Path,
+    format: ConfigFormat,
+    operation: &Operation,
+    content: &Value,
+) -> Result<(), String> {
+    let _lock = config_lock::lock(path).await?;
+    let (original, _) = config_lock::read_with_fingerprint(path).await?;
+    let serialized = render_config(original.as_deref(), format, content)?;
+    backup::snapshot(operation.clone(), path, original, serialized.clone()).await?;
+    atomic_write::write_file(path, serialized.into_bytes()).await
+}
 
-    let json_string = json_string_result?; // Handle the inner Result from the blocking task
+/// Read-modify-write a config file under the config lock. When another program
+/// changes the file between the read and the write, `edit` is replayed on the
+/// fresh contents; after a few attempts the conflict is reported instead. The
+/// replaced contents are snapshotted into the backup store for `operation`.
+pub async fn edit_config_file<T, F>(
+    path: &Path,
+    format: ConfigFormat,
+    operation: &Operation,
+    mut edit: F,
+) -> Result<T, String>
+where
+    F: FnMut(&mut Value) -> Result<T, String>,
+{
+    let _lock = config_lock::lock(path).await?;
 
-    // Write the JSON file asynchronously
-    fs::write(&path_buf, json_string)
-        .await
-        .map_err(|e| format!("Failed to write file: {}", e))
+    for attempt in 1..=config_lock::MAX_EDIT_ATTEMPTS {
+        let (original, fingerprint) = config_lock::read_with_fingerprint(path).await?;
+        let mut json = match &original {
+            Some(content) => parse_config(content, format)?,
+            None => json!({}),
+        };
+
+        let result = edit(&mut json)?;
+
+        let serialized = render_config(original.as_deref(), format, &json)?;
+        let snapshot =
+            backup::snapshot(operation.clone(), path, original, serialized.clone()).await?;
+        if atomic_write::write_file_if_unchanged(path, serialized.into_bytes(), fingerprint).await?
+        {
+            return Ok(result);
+        }
+        if let Some(snapshot) = snapshot {
+            backup::discard(&snapshot);
+        }
+        println!(
+            "[JsonManager] {} changed during edit (attempt {}/{})",
+            path.display(),
+            attempt,
+            config_lock::MAX_EDIT_ATTEMPTS
+        );
+    }
+
+    Err(config_lock::conflict_error(path))
 }
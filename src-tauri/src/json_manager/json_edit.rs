@@ -0,0 +1,676 @@
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::jsonc::strip_jsonc;
+
+// Format-preserving writes: instead of re-serializing the whole document, the
+// original text is parsed into spans and only the members whose value changed
+// are rewritten. Key order, indentation, comments and the trailing newline of
+// everything else stay as the user left them.
+
+/// Render `content` as JSON, editing `original` in place. A document that
+/// can't be edited that way is an error: rewriting it whole would reorder its
+/// keys and drop its comments. One that doesn't parse at all is written fresh.
+pub fn edit_json_text(original: Option<&str>, content: &Value) -> Result<String, String> {
+    let Some(original) = original.filter(|s| !s.trim().is_empty()) else {
+        return Ok(to_pretty(content, "  "));
+    };
+    let indent_unit = detect_indent(original);
+
+    let mut parser = Parser::new(original);
+    let mut root = match parser.parse_document() {
+        Ok(root) => root,
+        Err(_) if serde_json::from_str::<Value>(&strip_jsonc(original)).is_err() => {
+            let mut text = to_pretty(content, &indent_unit);
+            if original.ends_with('\n') {
+                text.push('\n');
+            }
+            return Ok(text);
+        }
+        Err(e) => return Err(format!("Failed to parse the existing config: {}", e)),
+    };
+    // An empty top-level object has no layout of its own; pretty-print into it
+    resolve_layout(&mut root, true);
+    let (Node::Object(object), Value::Object(new)) = (&root, content) else {
+        return Err("The existing config is not a JSON object".to_string());
+    };
+
+    let editor = Editor {
+        src: original,
+        indent_unit: &indent_unit,
+    };
+    let mut edits = Vec::new();
+    editor.diff_object(object, new, &mut edits);
+    if original.contains("\r\n") {
+        for edit in &mut edits {
+            edit.text = edit.text.replace('\n', "\r\n");
+        }
+    }
+    let edited = apply_edits(original, edits)
+        .ok_or_else(|| "Failed to edit the existing config: overlapping changes".to_string())?;
+
+    // Never hand back a document that doesn't say what the caller asked for
+    let reparsed: Option<Value> = serde_json::from_str(&strip_jsonc(&edited)).ok();
+    if reparsed.as_ref() != Some(content) {
+        return Err("Failed to edit the existing config in place".to_string());
+    }
+    Ok(edited)
+}
+
+fn to_pretty(content: &Value, indent_unit: &str) -> String {
+    let mut buf = Vec::new();
+    let formatter = PrettyFormatter::with_indent(indent_unit.as_bytes());
+    let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    // Serializing a Value into memory cannot fail
+    content.serialize(&mut ser).expect("serialize JSON value");
+    String::from_utf8(buf).expect("serde_json emits UTF-8")
+}
+
+/// Smallest indentation used in the document, defaulting to two spaces
+fn detect_indent(src: &str) -> String {
+    src.lines()
+        .filter_map(|line| {
+            let trimmed = line.trim_start_matches([' ', '\t']);
+            let ws = &line[..line.len() - trimmed.len()];
+            (!ws.is_empty() && !trimmed.is_empty()).then_some(ws)
+        })
+        .min_by_key(|ws| ws.len())
+        .map(|ws| {
+            if ws.starts_with('\t') {
+                "\t".to_string()
+            } else {
+                ws.to_string()
+            }
+        })
+        .unwrap_or_else(|| "  ".to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Span parser (accepts JSONC comments and trailing commas)
+// ---------------------------------------------------------------------------
+
+enum Node {
+    Object(ObjectNode),
+    Other { start: usize, end: usize },
+}
+
+impl Node {
+    fn span(&self) -> (usize, usize) {
+        match self {
+            Node::Object(o) => (o.start, o.end),
+            Node::Other { start, end } => (*start, *end),
+        }
+    }
+}
+
+struct ObjectNode {
+    /// Offset of `{`
+    start: usize,
+    /// Offset just past `}`
+    end: usize,
+    members: Vec<Member>,
+    /// Members sit on their own lines
+    multiline: bool,
+}
+
+struct Member {
+    key: String,
+    key_start: usize,
+    key_end: usize,
+    value: Node,
+    /// Offset of the comma following the value, if any
+    comma: Option<usize>,
+}
+
+impl Member {
+    fn value_end(&self) -> usize {
+        self.value.span().1
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            bytes: src.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Node, String> {
+        let node = self.parse_value()?;
+        self.skip_trivia()?;
+        if self.pos != self.bytes.len() {
+            return Err(format!("Unexpected content at offset {}", self.pos));
+        }
+        Ok(node)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected '{}' at offset {}",
+                byte as char, self.pos
+            ))
+        }
+    }
+
+    /// Skip whitespace and comments
+    fn skip_trivia(&mut self) -> Result<(), String> {
+        loop {
+            match (self.peek(), self.bytes.get(self.pos + 1)) {
+                (Some(b' ' | b'\t' | b'\n' | b'\r'), _) => self.pos += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    while self.peek().is_some_and(|b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    let rest = &self.src[self.pos + 2..];
+                    let close = rest
+                        .find("*/")
+                        .ok_or_else(|| "Unterminated comment".to_string())?;
+                    self.pos += 2 + close + 2;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Node, String> {
+        self.skip_trivia()?;
+        let start = self.pos;
+        match self.peek() {
+            Some(b'{') => self.parse_object().map(Node::Object),
+            Some(b'[') => {
+                self.parse_array()?;
+                Ok(Node::Other {
+                    start,
+                    end: self.pos,
+                })
+            }
+            Some(b'"') => {
+                self.parse_string()?;
+                Ok(Node::Other {
+                    start,
+                    end: self.pos,
+                })
+            }
+            Some(_) => {
+                while self.peek().is_some_and(|b| {
+                    !matches!(b, b',' | b'}' | b']' | b'/' | b' ' | b'\t' | b'\n' | b'\r')
+                }) {
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return Err(format!("Expected a value at offset {}", start));
+                }
+                Ok(Node::Other {
+                    start,
+                    end: self.pos,
+                })
+            }
+            None => Err("Unexpected end of document".to_string()),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<(), String> {
+        self.expect(b'"')?;
+        loop {
+            match self.peek() {
+                Some(b'\\') => self.pos += 2,
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(_) => self.pos += 1,
+                None => return Err("Unterminated string".to_string()),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<(), String> {
+        self.expect(b'[')?;
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some(b']') {
+                self.pos += 1;
+                return Ok(());
+            }
+            self.parse_value()?;
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {}
+                _ => return Err(format!("Expected ',' or ']' at offset {}", self.pos)),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<ObjectNode, String> {
+        let start = self.pos;
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some(b'}') {
+                self.pos += 1;
+                return Ok(ObjectNode {
+                    start,
+                    end: self.pos,
+                    multiline: self.src[start..self.pos].contains('\n'),
+                    members,
+                });
+            }
+
+            let key_start = self.pos;
+            self.parse_string()?;
+            let key_end = self.pos;
+            let key: String = serde_json::from_str(&self.src[key_start..key_end])
+                .map_err(|e| format!("Invalid key at offset {}: {}", key_start, e))?;
+
+            self.skip_trivia()?;
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            self.skip_trivia()?;
+            let comma = match self.peek() {
+                Some(b',') => {
+                    self.pos += 1;
+                    Some(self.pos - 1)
+                }
+                Some(b'}') => None,
+                _ => return Err(format!("Expected ',' or '}}' at offset {}", self.pos)),
+            };
+
+            members.push(Member {
+                key,
+                key_start,
+                key_end,
+                value,
+                comma,
+            });
+        }
+    }
+}
+
+/// Objects that are empty so far take the layout of the object around them
+fn resolve_layout(node: &mut Node, parent_multiline: bool) {
+    if let Node::Object(object) = node {
+        if object.members.is_empty() {
+            object.multiline = parent_multiline;
+        }
+        let multiline = object.multiline;
+        for member in &mut object.members {
+            resolve_layout(&mut member.value, multiline);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Diffing old spans against the new value
+// ---------------------------------------------------------------------------
+
+/// Replace `start..end` of the original text with `text`
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+struct Editor<'a> {
+    src: &'a str,
+    indent_unit: &'a str,
+}
+
+impl Editor<'_> {
+    fn diff_object(&self, object: &ObjectNode, new: &Map<String, Value>, edits: &mut Vec<Edit>) {
+        let members = &object.members;
+
+        // Like serde_json, the last occurrence of a duplicated key wins
+        let mut last_index: HashMap<&str, usize> = HashMap::new();
+        for (i, m) in members.iter().enumerate() {
+            last_index.insert(m.key.as_str(), i);
+        }
+        let kept: Vec<bool> = members
+            .iter()
+            .enumerate()
+            .map(|(i, m)| new.contains_key(&m.key) && last_index[m.key.as_str()] == i)
+            .collect();
+        let additions: Vec<(&String, &Value)> = new
+            .iter()
+            .filter(|(k, _)| !last_index.contains_key(k.as_str()))
+            .collect();
+
+        // Changed values of kept members
+        for (m, _) in members.iter().zip(&kept).filter(|(_, k)| **k) {
+            let new_value = &new[&m.key];
+            match (&m.value, new_value) {
+                (Node::Object(child), Value::Object(new_child)) => {
+                    self.diff_object(child, new_child, edits)
+                }
+                (node, _) => {
+                    let (start, end) = node.span();
+                    if self.value_at(start, end).as_ref() != Some(new_value) {
+                        edits.push(Edit {
+                            start,
+                            end,
+                            text: self.render(new_value, object, m.key_start),
+                        });
+                    }
+                }
+            }
+        }
+
+        let multiline = object.multiline;
+        let indent = self.member_indent(object);
+
+        if !kept.contains(&true) {
+            if members.is_empty() && additions.is_empty() {
+                return;
+            }
+            let body = if additions.is_empty() {
+                String::new()
+            } else if multiline {
+                let items: Vec<String> = additions
+                    .iter()
+                    .map(|(k, v)| format!("\n{}{}", indent, self.member_text(k, v, object)))
+                    .collect();
+                format!(
+                    "{}\n{}",
+                    items.join(","),
+                    line_indent(self.src, object.start)
+                )
+            } else {
+                additions
+                    .iter()
+                    .map(|(k, v)| self.member_text(k, v, object))
+                    .collect::<Vec<_>>()
+                    .join(&self.item_separator(object))
+            };
+            edits.push(Edit {
+                start: object.start + 1,
+                end: object.end - 1,
+                text: body,
+            });
+            return;
+        }
+
+        // Runs of removed members
+        let mut i = 0;
+        while i < members.len() {
+            if kept[i] {
+                i += 1;
+                continue;
+            }
+            let mut j = i;
+            while j + 1 < members.len() && !kept[j + 1] {
+                j += 1;
+            }
+            match members[j].comma {
+                Some(comma) if j + 1 < members.len() => edits.push(Edit {
+                    start: members[i].key_start,
+                    end: skip_whitespace(self.src, end_of_line_comment(self.src, comma + 1)),
+                    text: String::new(),
+                }),
+                // The run reaches the end: drop it along with the comma before it
+                _ => edits.push(Edit {
+                    start: members[i - 1].value_end(),
+                    end: members[j].value_end(),
+                    text: String::new(),
+                }),
+            }
+            i = j + 1;
+        }
+
+        if additions.is_empty() {
+            return;
+        }
+        let last_kept = kept.iter().rposition(|k| *k).unwrap();
+        let trailing_comma = match members[last_kept].comma {
+            Some(comma) if last_kept + 1 == members.len() => Some(comma),
+            _ => None,
+        };
+        let separator = self.item_separator(object);
+        let text: String = additions
+            .iter()
+            .map(|(k, v)| {
+                let member = self.member_text(k, v, object);
+                match (trailing_comma.is_some(), multiline) {
+                    (true, true) => format!("\n{}{},", indent, member),
+                    (true, false) => format!(" {},", member),
+                    (false, true) => format!(",\n{}{}", indent, member),
+                    (false, false) => format!("{}{}", separator, member),
+                }
+            })
+            .collect();
+        let pos = match trailing_comma {
+            Some(comma) => end_of_line_comment(self.src, comma + 1),
+            None => members[last_kept].value_end(),
+        };
+        edits.push(Edit {
+            start: pos,
+            end: pos,
+            text,
+        });
+    }
+
+    fn value_at(&self, start: usize, end: usize) -> Option<Value> {
+        serde_json::from_str(&strip_jsonc(&self.src[start..end])).ok()
+    }
+
+    fn member_indent(&self, object: &ObjectNode) -> String {
+        match object.members.first() {
+            Some(first) => line_indent(self.src, first.key_start).to_string(),
+            None => format!(
+                "{}{}",
+                line_indent(self.src, object.start),
+                self.indent_unit
+            ),
+        }
+    }
+
+    fn item_separator(&self, object: &ObjectNode) -> String {
+        match object.members.first().and_then(|m| m.comma) {
+            Some(comma) if self.src[comma + 1..].starts_with(' ') => ", ".to_string(),
+            Some(_) => ",".to_string(),
+            None if self.key_separator(object) == ":" => ",".to_string(),
+            None => ", ".to_string(),
+        }
+    }
+
+    fn key_separator(&self, object: &ObjectNode) -> &'static str {
+        match object.members.first() {
+            Some(m) if !self.src[m.key_end..m.value.span().0].contains(' ') => ":",
+            _ => ": ",
+        }
+    }
+
+    fn member_text(&self, key: &str, value: &Value, object: &ObjectNode) -> String {
+        let indent = self.member_indent(object);
+        let value_text = if object.multiline {
+            self.indented(value, &indent)
+        } else {
+            value.to_string()
+        };
+        format!(
+            "{}{}{}",
+            Value::from(key),
+            self.key_separator(object),
+            value_text
+        )
+    }
+
+    /// Render a replacement value for the member whose key starts at `key_start`
+    fn render(&self, value: &Value, object: &ObjectNode, key_start: usize) -> String {
+        if object.multiline {
+            self.indented(value, line_indent(self.src, key_start))
+        } else {
+            value.to_string()
+        }
+    }
+
+    fn indented(&self, value: &Value, indent: &str) -> String {
+        to_pretty(value, self.indent_unit).replace('\n', &format!("\n{}", indent))
+    }
+}
+
+/// Leading whitespace of the line containing `pos`
+fn line_indent(src: &str, pos: usize) -> &str {
+    let line_start = src[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let line = &src[line_start..];
+    let trimmed = line.trim_start_matches([' ', '\t']);
+    &line[..line.len() - trimmed.len()]
+}
+
+/// Skip a `// comment` that follows `pos` on the same line
+fn end_of_line_comment(src: &str, pos: usize) -> usize {
+    let rest = &src[pos..];
+    let after_spaces = rest.trim_start_matches([' ', '\t']);
+    if after_spaces.starts_with("//") {
+        // Stop before the line ending, `\r\n` included
+        let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        pos + line.trim_end_matches('\r').len()
+    } else {
+        pos
+    }
+}
+
+fn skip_whitespace(src: &str, pos: usize) -> usize {
+    let rest = &src[pos..];
+    pos + (rest.len() - rest.trim_start().len())
+}
+
+fn apply_edits(src: &str, mut edits: Vec<Edit>) -> Option<String> {
+    // Apply back to front so earlier offsets stay valid. At the same offset the
+    // removal goes first, then the insertion lands where it was.
+    edits.sort_by(|a, b| b.start.cmp(&a.start).then(b.end.cmp(&a.end)));
+    let mut out = src.to_string();
+    let mut limit = src.len();
+    for edit in edits {
+        if edit.end > limit {
+            return None;
+        }
+        out.replace_range(edit.start..edit.end, &edit.text);
+        limit = edit.start;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edit(original: &str, content: Value) -> String {
+        edit_json_text(Some(original), &content).unwrap()
+    }
+
+    #[test]
+    fn keeps_key_order_and_indentation() {
+        let original = "{\n    \"zeta\": 1,\n    \"alpha\": {\n        \"b\": true,\n        \"a\": \"x\"\n    }\n}\n";
+        let edited = edit(original, json!({"zeta": 2, "alpha": {"b": true, "a": "x"}}));
+        assert_eq!(
+            edited,
+            "{\n    \"zeta\": 2,\n    \"alpha\": {\n        \"b\": true,\n        \"a\": \"x\"\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn keeps_crlf_comments_and_trailing_commas() {
+        let original = "{\r\n  // servers\r\n  \"mcpServers\": {\r\n    /* main */\r\n    \"a\": {\"command\": \"x\"}, // keep\r\n  },\r\n}\r\n";
+        let edited = edit(
+            original,
+            json!({"mcpServers": {"a": {"command": "y"}, "b": {"url": "u"}}}),
+        );
+        assert_eq!(
+            edited,
+            "{\r\n  // servers\r\n  \"mcpServers\": {\r\n    /* main */\r\n    \"a\": {\"command\": \"y\"}, // keep\r\n    \"b\": {\r\n      \"url\": \"u\"\r\n    },\r\n  },\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn removes_a_commented_member_with_crlf() {
+        let original = "{\r\n  \"a\": 1, // first\r\n  \"b\": 2\r\n}\r\n";
+        assert_eq!(edit(original, json!({"b": 2})), "{\r\n  \"b\": 2\r\n}\r\n");
+    }
+
+    #[test]
+    fn removes_first_middle_and_last_member() {
+        let original = "{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 3\n}";
+        assert_eq!(
+            edit(original, json!({"b": 2, "c": 3})),
+            "{\n  \"b\": 2,\n  \"c\": 3\n}"
+        );
+        assert_eq!(
+            edit(original, json!({"a": 1, "c": 3})),
+            "{\n  \"a\": 1,\n  \"c\": 3\n}"
+        );
+        assert_eq!(
+            edit(original, json!({"a": 1, "b": 2})),
+            "{\n  \"a\": 1,\n  \"b\": 2\n}"
+        );
+        assert_eq!(edit(original, json!({})), "{}");
+    }
+
+    #[test]
+    fn adds_to_empty_and_inline_objects() {
+        assert_eq!(
+            edit("{}\n", json!({"mcpServers": {"a": {"command": "x"}}})),
+            "{\n  \"mcpServers\": {\n    \"a\": {\n      \"command\": \"x\"\n    }\n  }\n}\n"
+        );
+        assert_eq!(
+            edit(
+                "{\n  \"mcpServers\": {}\n}",
+                json!({"mcpServers": {"a": 1}})
+            ),
+            "{\n  \"mcpServers\": {\n    \"a\": 1\n  }\n}"
+        );
+        assert_eq!(
+            edit(
+                "{\n  \"env\": {\"A\": \"1\"}\n}",
+                json!({"env": {"A": "1", "B": "2"}})
+            ),
+            "{\n  \"env\": {\"A\": \"1\", \"B\": \"2\"}\n}"
+        );
+    }
+
+    #[test]
+    fn unchanged_content_is_left_alone() {
+        let original = "{\"b\":1,\n\t\"a\" : [1, 2 ] // note\n}";
+        assert_eq!(edit(original, json!({"b": 1, "a": [1, 2]})), original);
+    }
+
+    #[test]
+    fn refuses_to_rewrite_what_it_cannot_edit_in_place() {
+        let content = json!({"mcpServers": {}});
+        assert!(edit_json_text(Some("[1, 2]"), &content).is_err());
+        assert!(edit_json_text(Some("{\"a\": 1}"), &json!([1])).is_err());
+    }
+
+    #[test]
+    fn writes_fresh_when_there_is_nothing_to_keep() {
+        let content = json!({"a": 1});
+        let expected = "{\n  \"a\": 1\n}";
+        assert_eq!(edit_json_text(None, &content).unwrap(), expected);
+        assert_eq!(edit_json_text(Some("  \n"), &content).unwrap(), expected);
+        assert_eq!(
+            edit_json_text(Some("{\"a\": \n"), &content).unwrap(),
+            format!("{}\n", expected)
+        );
+    }
+}
@@ -0,0 +1,107 @@
+/// Turn JSONC (JSON with `//` and `/* */` comments and trailing commas) into plain JSON
+pub fn strip_jsonc(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+    // Index in `out` of the last comma that may turn out to be a trailing one
+    let mut pending_comma: Option<usize> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '"' => {
+                pending_comma = None;
+                out.push(c);
+                i += 1;
+                while i < chars.len() {
+                    let sc = chars[i];
+                    out.push(sc);
+                    i += 1;
+                    if sc == '\\' && i < chars.len() {
+                        out.push(chars[i]);
+                        i += 1;
+                    } else if sc == '"' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            ',' => {
+                pending_comma = Some(out.len());
+                out.push(c);
+                i += 1;
+            }
+            '}' | ']' => {
+                if let Some(pos) = pending_comma.take() {
+                    out.replace_range(pos..pos + 1, " ");
+                }
+                out.push(c);
+                i += 1;
+            }
+            c if c.is_whitespace() => {
+                out.push(c);
+                i += 1;
+            }
+            _ => {
+                pending_comma = None;
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn parse(input: &str) -> Value {
+        serde_json::from_str(&strip_jsonc(input)).unwrap()
+    }
+
+    #[test]
+    fn keeps_comment_markers_inside_strings() {
+        assert_eq!(
+            parse(r#"{"url": "http://localhost/*x*/", "q": "a\"//b"} // note"#),
+            json!({"url": "http://localhost/*x*/", "q": "a\"//b"})
+        );
+    }
+
+    #[test]
+    fn strips_line_and_block_comments() {
+        let input =
+            "{\n  // servers\n  \"a\": /* inline */ 1,\n  /* multi\n     line, } */\n  \"b\": 2\n}";
+        assert_eq!(parse(input), json!({"a": 1, "b": 2}));
+    }
+
+    #[test]
+    fn drops_trailing_commas() {
+        assert_eq!(
+            parse("{\"a\": [1, 2,], \"b\": {\"c\": 3,},}"),
+            json!({"a": [1, 2], "b": {"c": 3}})
+        );
+        assert_eq!(parse("[\n  1, // one\n  /* two */\n]"), json!([1]));
+    }
+
+    #[test]
+    fn keeps_commas_inside_strings() {
+        assert_eq!(
+            parse(r#"{"a": ",", "b": ",}"}"#),
+            json!({"a": ",", "b": ",}"})
+        );
+    }
+}
@@ -1,13 +1,20 @@
 use serde_json::Value;
 use std::path::Path;
 
+use crate::backend::ServerSet;
+use crate::backup::Operation;
+
 // Module declarations
 pub mod file_io;
+pub mod json_edit;
+pub mod jsonc;
 pub mod server_crud;
+pub mod server_set;
 pub mod server_state;
 pub mod utils;
 
 // Re-exports for convenience
+pub use file_io::ConfigFormat;
 
 /// Main JsonManager struct that provides a unified interface for all JSON operations
 pub struct JsonManager;
@@ -22,6 +29,32 @@ impl JsonManager {
         file_io::write_json_file(path, content).await
     }
 
+    pub async fn read_config_file(path: &Path, format: ConfigFormat) -> Result<Value, String> {
+        file_io::read_config_file(path, format).await
+    }
+
+    pub async fn write_config_file(
+        path: &Path,
+        format: ConfigFormat,
+        operation: &Operation,
+        content: &Value,
+    ) -> Result<(), String> {
+        file_io::write_config_file(path, format, operation, content).await
+    }
+
+    // Normalized server set operations
+    pub async fn read_server_set(path: &Path, client: &str) -> Result<ServerSet, String> {
+        server_set::read_server_set(path, client).await
+    }
+
+    pub async fn write_server_set(
+        path: &Path,
+        client: &str,
+        servers: &ServerSet,
+    ) -> Result<(), String> {
+        server_set::write_server_set(path, client, servers).await
+    }
+
     // Server CRUD operations
     pub async fn add_mcp_server(
         path: &Path,
@@ -0,0 +1,93 @@
+use serde_json::{Map, Value, json};
+use std::path::Path;
+
+use super::file_io::{edit_config_file, read_config_file};
+use super::utils::client_layout;
+use crate::backend::ServerSet;
+use crate::backup::Operation;
+
+/// Read active and disabled servers, regardless of how the client marks disabled ones
+pub async fn read_server_set(path: &Path, client: &str) -> Result<ServerSet, String> {
+    let layout = client_layout(client);
+    let json = read_config_file(path, layout.format).await?;
+    let key = layout.servers_key.as_str();
+
+    let servers = json
+        .get(key)
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default();
+
+    if layout.per_server_disabled() {
+        // For clients like 'cline', split out servers with disabled: true
+        let mut set = ServerSet::default();
+        for (name, mut server) in servers {
+            let disabled = server
+                .get("disabled")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            if disabled {
+                if let Some(obj) = server.as_object_mut() {
+                    obj.remove("disabled");
+                }
+                set.disabled.insert(name, server);
+            } else {
+                set.active.insert(name, server);
+            }
+        }
+        return Ok(set);
+    }
+
+    Ok(ServerSet {
+        active: servers,
+        disabled: json
+            .get("__disabled")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default(),
+    })
+}
+
+/// Replace active and disabled servers, keeping the rest of the file
+pub async fn write_server_set(path: &Path, client: &str, set: &ServerSet) -> Result<(), String> {
+    let layout = client_layout(client);
+    let key = layout.servers_key.as_str();
+
+    let operation = Operation::new(client, "replace_servers");
+    edit_config_file(path, layout.format, &operation, |json| {
+        if !json.is_object() {
+            *json = json!({});
+        }
+
+        if layout.per_server_disabled() {
+            // For clients like 'cline', keep disabled servers in place with disabled: true
+            let mut servers: Map<String, Value> = set.active.clone();
+            for (name, server) in &set.disabled {
+                let mut server = server.clone();
+                server["disabled"] = json!(true);
+                servers.insert(name.clone(), server);
+            }
+            json[key] = Value::Object(servers);
+            json.as_object_mut().unwrap().remove("__disabled");
+        } else if !layout.supports_disabled() {
+            // The client has no way to keep disabled servers, so they are dropped
+            if !set.disabled.is_empty() {
+                println!(
+                    "[JsonManager] {} does not support disabled servers, skipping: {:?}",
+                    client,
+                    set.disabled.keys().collect::<Vec<_>>()
+                );
+            }
+            json[key] = Value::Object(set.active.clone());
+        } else {
+            json[key] = Value::Object(set.active.clone());
+            if set.disabled.is_empty() {
+                json.as_object_mut().unwrap().remove("__disabled");
+            } else {
+                json["__disabled"] = Value::Object(set.disabled.clone());
+            }
+        }
+        Ok(())
+    })
+    .await
+}
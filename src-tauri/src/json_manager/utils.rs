@@ -1,12 +1,66 @@
 use serde_json::Value;
 
+use super::file_io::ConfigFormat;
+use crate::backend::DisabledStyle;
+use crate::client_descriptor::get_descriptor;
+
+/// Where a client keeps its servers, how it marks disabled ones and its file format
+#[derive(Debug, Clone)]
+pub struct ClientLayout {
+    pub servers_key: String,
+    pub disabled_style: DisabledStyle,
+    pub format: ConfigFormat,
+}
+
+impl ClientLayout {
+    /// True if the client uses per-server 'disabled' key instead of global __disabled section
+    pub fn per_server_disabled(&self) -> bool {
+        self.disabled_style == DisabledStyle::DisabledFlag
+    }
+
+    pub fn supports_disabled(&self) -> bool {
+        self.disabled_style != DisabledStyle::Unsupported
+    }
+}
+
+/// Get the layout of a built-in or descriptor-defined client
+pub fn client_layout(client: &str) -> ClientLayout {
+    if let Some(descriptor) = get_descriptor(client) {
+        return descriptor.layout();
+    }
+
+    let servers_key = if client == "copilot" {
+        "servers"
+    } else {
+        "mcpServers"
+    };
+    let disabled_style = if matches!(client, "cline" | "roo_code") {
+        DisabledStyle::DisabledFlag
+    } else {
+        DisabledStyle::Section
+    };
+
+    // VS Code and its extensions accept comments and trailing commas
+    let format = if matches!(client, "copilot" | "cline" | "roo_code") {
+        ConfigFormat::Jsonc
+    } else {
+        ConfigFormat::Json
+    };
+
+    ClientLayout {
+        servers_key: servers_key.to_string(),
+        disabled_style,
+        format,
+    }
+}
+
 /// Normalize response key to mcpServers for consistent client API
-pub fn normalize_response_key(mut json: Value, client: &str) -> Result<Value, String> {
-    if client == "copilot" && json.is_object() {
-        let servers_key = "servers";
-        let mcp_servers_key = "mcpServers";
+pub fn normalize_response_key(mut json: Value, layout: &ClientLayout) -> Result<Value, String> {
+    let servers_key = layout.servers_key.as_str();
+    let mcp_servers_key = "mcpServers";
 
-        // If json has "servers" key but not "mcpServers"
+    if servers_key != mcp_servers_key && json.is_object() {
+        // If json has the client's servers key but not "mcpServers"
         if json.as_object().unwrap().contains_key(servers_key)
             && !json.as_object().unwrap().contains_key(mcp_servers_key)
         {
@@ -18,18 +72,3 @@ pub fn normalize_response_key(mut json: Value, client: &str) -> Result<Value, St
 
     Ok(json)
 }
-
-/// Get the appropriate key name based on client type
-pub fn get_key_by_client(client: &str) -> &str {
-    if client == "copilot" {
-        "servers"
-    } else {
-        "mcpServers"
-    }
-}
-
-/// Returns true if the client uses per-server 'disabled' key instead of global __disabled section
-pub fn is_per_server_disabled_client(client: &str) -> bool {
-    matches!(client, "cline" | "roo_code")
-}
-
@@ -6,13 +6,18 @@
 use std::env;
 use std::sync::{Arc, Mutex};
 
-mod adapter;
+mod atomic_write;
+mod backend;
+mod backup;
 mod claude_code_commands;
 mod claude_disabled;
 mod client;
+mod client_descriptor;
 mod cmd;
 mod codex;
 mod config;
+mod config_lock;
+mod detect;
 mod dxt;
 mod encryption;
 mod git;
@@ -21,6 +26,9 @@ mod json_manager;
 mod mcp_commands;
 mod mcp_crud;
 mod mcp_sync;
+mod server_meta;
+#[cfg(test)]
+mod test_home;
 mod window;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -53,11 +61,36 @@ pub fn run() {
             mcp_crud::remove_mcp_server,
             mcp_crud::update_mcp_server,
             mcp_crud::batch_delete_mcp_servers,
+            mcp_crud::preview_add_mcp_server,
+            mcp_crud::preview_remove_mcp_server,
+            mcp_crud::preview_update_mcp_server,
+            mcp_crud::preview_batch_delete_mcp_servers,
             mcp_commands::disable_mcp_server,
             mcp_commands::enable_mcp_server,
             mcp_commands::list_disabled_servers,
             mcp_commands::update_disabled_mcp_server,
+            mcp_commands::preview_disable_mcp_server,
+            mcp_commands::preview_enable_mcp_server,
+            mcp_commands::preview_update_disabled_mcp_server,
+            mcp_commands::get_client_capabilities,
+            client_descriptor::list_client_descriptors,
+            client_descriptor::reload_client_descriptors,
+            detect::detect_clients,
+            backup::list_backups,
+            backup::diff_backup,
+            backup::restore_backup,
+            backup::get_backup_retention,
+            backup::set_backup_retention,
             mcp_sync::sync_mcp_config,
+            mcp_sync::fan_out::sync_mcp_config_to_targets,
+            mcp_sync::tags::list_server_tags,
+            mcp_sync::tags::set_server_tags,
+            mcp_sync::links::list_sync_links,
+            mcp_sync::links::create_sync_link,
+            mcp_sync::links::set_sync_link_paused,
+            mcp_sync::links::remove_sync_link,
+            server_meta::migrate_server_metadata,
+            server_meta::get_server_metadata,
             installer::check_command_exists,
             installer::install_command,
             git::git_clone,
@@ -75,6 +108,10 @@ pub fn run() {
             claude_code_commands::claude_mcp_get,
             claude_code_commands::claude_mcp_add,
             claude_code_commands::claude_mcp_remove,
+            claude_code_commands::claude_mcpjson_list,
+            claude_code_commands::claude_mcpjson_approve,
+            claude_code_commands::claude_mcpjson_reject,
+            claude_code_commands::claude_mcpjson_set_approve_all,
             claude_code_commands::claude_list_projects,
             claude_code_commands::check_claude_cli_available,
             claude_code_commands::check_claude_config_exists,
@@ -82,6 +119,7 @@ pub fn run() {
             claude_disabled::claude_disable_server,
             claude_disabled::claude_enable_server,
             claude_disabled::claude_update_disabled,
+            codex::list_codex_profiles,
         ])
         .manage(Arc::new(Mutex::new(None::<String>)))
         .setup(|_app| {
@@ -90,6 +128,15 @@ pub fn run() {
                 use tauri_plugin_deep_link::DeepLinkExt;
                 _app.deep_link().register_all()?;
             }
+            tauri::async_runtime::spawn(async {
+                // Starting the links syncs them, which writes the same configs
+                if let Err(e) = server_meta::migrate_once().await {
+                    println!("[Meta] Metadata migration failed: {}", e);
+                }
+                if let Err(e) = mcp_sync::watch::start().await {
+                    println!("[Watch] Failed to start sync links: {}", e);
+                }
+            });
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -1,4 +1,6 @@
-use crate::adapter::ClientAdapter;
+use crate::backend::preview::{self, Preview, ServerChange};
+use crate::backend::{Capabilities, get_backend};
+use crate::server_meta;
 use serde_json::Value;
 
 #[tauri::command]
@@ -7,8 +9,8 @@ pub async fn disable_mcp_server(
     path: Option<String>,
     server_name: String,
 ) -> Result<Value, String> {
-    let adapter = ClientAdapter::new(&client_name, path.as_deref());
-    adapter.disable(server_name).await
+    let backend = get_backend(&client_name, path.as_deref())?;
+    backend.disable(server_name).await
 }
 
 #[tauri::command]
@@ -17,8 +19,8 @@ pub async fn enable_mcp_server(
     path: Option<String>,
     server_name: String,
 ) -> Result<Value, String> {
-    let adapter = ClientAdapter::new(&client_name, path.as_deref());
-    adapter.enable(server_name).await
+    let backend = get_backend(&client_name, path.as_deref())?;
+    backend.enable(server_name).await
 }
 
 #[tauri::command]
@@ -26,8 +28,8 @@ pub async fn list_disabled_servers(
     client_name: String,
     path: Option<String>,
 ) -> Result<Value, String> {
-    let adapter = ClientAdapter::new(&client_name, path.as_deref());
-    adapter.list_disabled().await
+    let backend = get_backend(&client_name, path.as_deref())?;
+    backend.list_disabled().await
 }
 
 #[tauri::command]
@@ -35,8 +37,59 @@ pub async fn update_disabled_mcp_server(
     client_name: String,
     path: Option<String>,
     server_name: String,
-    server_config: Value,
+    mut server_config: Value,
 ) -> Result<Value, String> {
-    let adapter = ClientAdapter::new(&client_name, path.as_deref());
-    adapter.update_disabled(server_name, server_config).await
+    let backend = get_backend(&client_name, path.as_deref())?;
+    server_meta::strip_injected(&mut server_config);
+    let result = backend
+        .update_disabled(server_name.clone(), server_config)
+        .await?;
+    server_meta::record(&client_name, &backend.location(), &[server_name], false).await;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_client_capabilities(
+    client_name: String,
+    path: Option<String>,
+) -> Result<Capabilities, String> {
+    let backend = get_backend(&client_name, path.as_deref())?;
+    Ok(backend.capabilities())
+}
+
+#[tauri::command]
+pub async fn preview_disable_mcp_server(
+    client_name: String,
+    path: Option<String>,
+    server_name: String,
+) -> Result<Preview, String> {
+    let backend = get_backend(&client_name, path.as_deref())?;
+    let change = ServerChange::Disable { name: server_name };
+    preview::preview(backend.as_ref(), &client_name, change).await
+}
+
+#[tauri::command]
+pub async fn preview_enable_mcp_server(
+    client_name: String,
+    path: Option<String>,
+    server_name: String,
+) -> Result<Preview, String> {
+    let backend = get_backend(&client_name, path.as_deref())?;
+    let change = ServerChange::Enable { name: server_name };
+    preview::preview(backend.as_ref(), &client_name, change).await
+}
+
+#[tauri::command]
+pub async fn preview_update_disabled_mcp_server(
+    client_name: String,
+    path: Option<String>,
+    server_name: String,
+    server_config: Value,
+) -> Result<Preview, String> {
+    let backend = get_backend(&client_name, path.as_deref())?;
+    let change = ServerChange::UpdateDisabled {
+        name: server_name,
+        config: server_config,
+    };
+    preview::preview(backend.as_ref(), &client_name, change).await
 }
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use crate::atomic_write;
+use crate::backend::ServerSet;
+use crate::config_lock::fnv1a;
+
+// The merge base of a (source, target) pair: both sides as they were right
+// after their last sync, in ~/.config/mcplinker/sync/<source>-<target>-<hash>.json
+
+/// One end of a sync
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub client: String,
+    pub path: Option<String>,
+}
+
+impl Endpoint {
+    pub fn new(client: &str, path: Option<&str>) -> Self {
+        Self {
+            client: client.to_string(),
+            path: path.filter(|p| !p.is_empty()).map(|p| p.to_string()),
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("{}\0{}", self.client, self.path.as_deref().unwrap_or(""))
+    }
+}
+
+/// Both sides of a pair after their last sync
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncBase {
+    pub source: ServerSet,
+    pub target: ServerSet,
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+fn base_path(source: &Endpoint, target: &Endpoint) -> Result<PathBuf, String> {
+    let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    let safe = |s: &str| -> String {
+        s.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    };
+    let hash = fnv1a(format!("{}\0{}", source.key(), target.key()).as_bytes());
+    Ok(home.join(".config/mcplinker/sync").join(format!(
+        "{}-{}-{:016x}.json",
+        safe(&source.client),
+        safe(&target.client),
+        hash
+    )))
+}
+
+/// Load the base of a pair; empty when the pair was never synced
+pub fn load_base(source: &Endpoint, target: &Endpoint) -> Result<SyncBase, String> {
+    let path = base_path(source, target)?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse sync base {}: {}", path.display(), e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(SyncBase::default()),
+        Err(e) => Err(format!("Failed to read sync base: {}", e)),
+    }
+}
+
+pub fn save_base(source: &Endpoint, target: &Endpoint, base: &SyncBase) -> Result<(), String> {
+    let path = base_path(source, target)?;
+    let content = serde_json::to_string_pretty(base)
+        .map_err(|e| format!("Failed to serialize sync base: {}", e))?;
+    atomic_write::write_file_blocking(&path, content.as_bytes())
+}
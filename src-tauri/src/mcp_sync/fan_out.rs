@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+use super::base::Endpoint;
+use super::filter::{ServerFilter, SyncFilter};
+use super::report::SyncReport;
+use super::strategy::ConflictStrategy;
+use super::{PlannedSync, SyncMode, commit_sync, plan_sync, write_sync};
+use crate::backend::get_backend;
+
+/// One client to sync to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncTarget {
+    pub client_name: String,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetStatus {
+    /// Dry run: computed, nothing written
+    Planned,
+    Synced,
+    /// Already in sync, nothing to write
+    Unchanged,
+    /// Preparing or writing this target failed
+    Failed,
+    /// Written, then restored because another target failed
+    RolledBack,
+    /// Written, and restoring it after another failure failed as well
+    RollbackFailed,
+    /// Not written because another target failed first
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetSyncResult {
+    pub client_name: String,
+    pub path: Option<String>,
+    pub status: TargetStatus,
+    pub report: Option<SyncReport>,
+    pub error: Option<String>,
+}
+
+/// Outcome of a fan-out sync; `committed` is false when it was rolled back
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FanOutResult {
+    pub committed: bool,
+    pub targets: Vec<TargetSyncResult>,
+}
+
+fn target_result(
+    end: &Endpoint,
+    status: TargetStatus,
+    report: Option<SyncReport>,
+    error: Option<String>,
+) -> TargetSyncResult {
+    TargetSyncResult {
+        client_name: end.client.clone(),
+        path: end.path.clone(),
+        status,
+        report,
+        error,
+    }
+}
+
+/// Sync one source to several targets as a unit. Every target is read and
+/// planned before anything is written; if a write fails, the targets already
+/// written get their previous servers back.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn sync_mcp_config_to_targets(
+    from_client: String,
+    from_path: Option<String>,
+    targets: Vec<SyncTarget>,
+    override_all: bool,
+    mode: Option<SyncMode>,
+    conflict_strategy: Option<ConflictStrategy>,
+    filter: Option<SyncFilter>,
+    dry_run: Option<bool>,
+) -> Result<FanOutResult, String> {
+    let mode = SyncMode::resolve(mode, override_all);
+    let filter = ServerFilter::new(filter)?;
+    let source_end = Endpoint::new(&from_client, from_path.as_deref());
+    let source = get_backend(&from_client, from_path.as_deref())?;
+    let from = source.read_servers().await?;
+
+    let ends: Vec<Endpoint> = targets
+        .iter()
+        .map(|t| Endpoint::new(&t.client_name, t.path.as_deref()))
+        .collect();
+    for (i, end) in ends.iter().enumerate() {
+        if ends[..i].contains(end) {
+            return Err(format!("Target '{}' is listed twice", end.client));
+        }
+    }
+
+    // Prepare every target first, so a bad config fails the sync before any write
+    let mut plans: Vec<PlannedSync> = Vec::new();
+    let mut failure = None;
+    for end in &ends {
+        match plan_sync(
+            &source_end,
+            &from,
+            end.clone(),
+            mode,
+            &filter,
+            conflict_strategy,
+        )
+        .await
+        {
+            Ok(plan) => plans.push(plan),
+            Err(e) => {
+                println!("[Sync] Failed to prepare {}: {}", end.client, e);
+                failure = Some((end.clone(), e));
+                break;
+            }
+        }
+    }
+    if let Some((failed, error)) = failure {
+        let targets = ends
+            .iter()
+            .map(|end| {
+                if *end == failed {
+                    target_result(end, TargetStatus::Failed, None, Some(error.clone()))
+                } else {
+                    target_result(end, TargetStatus::Skipped, None, None)
+                }
+            })
+            .collect();
+        return Ok(FanOutResult {
+            committed: false,
+            targets,
+        });
+    }
+
+    if dry_run.unwrap_or(false) {
+        let targets = plans
+            .iter()
+            .map(|plan| {
+                target_result(
+                    &plan.target_end,
+                    TargetStatus::Planned,
+                    Some(plan.report.clone()),
+                    None,
+                )
+            })
+            .collect();
+        return Ok(FanOutResult {
+            committed: false,
+            targets,
+        });
+    }
+
+    // Write them all, remembering which ones were changed
+    let mut statuses = vec![TargetStatus::Skipped; plans.len()];
+    let mut errors: Vec<Option<String>> = vec![None; plans.len()];
+    let mut failed = false;
+    for (i, plan) in plans.iter().enumerate() {
+        match write_sync(plan).await {
+            Ok(true) => statuses[i] = TargetStatus::Synced,
+            Ok(false) => statuses[i] = TargetStatus::Unchanged,
+            Err(e) => {
+                println!("[Sync] Failed to write {}: {}", plan.target_end.client, e);
+                statuses[i] = TargetStatus::Failed;
+                errors[i] = Some(e);
+                failed = true;
+                break;
+            }
+        }
+    }
+
+    if failed {
+        // Undo in reverse order, so targets sharing a file end up as they started
+        for (i, plan) in plans.iter().enumerate().rev() {
+            if statuses[i] != TargetStatus::Synced {
+                continue;
+            }
+            match plan.target.write_servers(&plan.current).await {
+                Ok(()) => statuses[i] = TargetStatus::RolledBack,
+                Err(e) => {
+                    println!(
+                        "[Sync] Failed to roll back {}: {}",
+                        plan.target_end.client, e
+                    );
+                    statuses[i] = TargetStatus::RollbackFailed;
+                    errors[i] = Some(e);
+                }
+            }
+        }
+    } else {
+        for plan in &plans {
+            commit_sync(&source_end, &from, plan).await?;
+        }
+    }
+
+    let targets = plans
+        .iter()
+        .zip(statuses)
+        .zip(errors)
+        .map(|((plan, status), error)| {
+            target_result(&plan.target_end, status, Some(plan.report.clone()), error)
+        })
+        .collect();
+    Ok(FanOutResult {
+        committed: !failed,
+        targets,
+    })
+}
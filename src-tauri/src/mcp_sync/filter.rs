@@ -0,0 +1,126 @@
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+
+use super::tags::{ServerTags, load_tags};
+
+/// Which servers a sync touches. A server is selected when it matches
+/// `include` or carries one of `includeTags` (or when both are empty), and
+/// matches neither `exclude` nor `excludeTags`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SyncFilter {
+    /// Server names or glob patterns such as `internal-*`
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub include_tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+}
+
+/// A [`SyncFilter`] with its patterns parsed and the tags loaded
+#[derive(Default)]
+pub struct ServerFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    include_tags: Vec<String>,
+    exclude_tags: Vec<String>,
+    tags: ServerTags,
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Pattern>, String> {
+    patterns
+        .iter()
+        .map(|p| Pattern::new(p).map_err(|e| format!("Invalid server pattern '{}': {}", p, e)))
+        .collect()
+}
+
+impl ServerFilter {
+    pub fn new(filter: Option<SyncFilter>) -> Result<Self, String> {
+        let Some(filter) = filter else {
+            return Ok(Self::default());
+        };
+        let uses_tags = !filter.include_tags.is_empty() || !filter.exclude_tags.is_empty();
+        Ok(Self {
+            include: compile_patterns(&filter.include)?,
+            exclude: compile_patterns(&filter.exclude)?,
+            include_tags: filter.include_tags,
+            exclude_tags: filter.exclude_tags,
+            tags: if uses_tags {
+                load_tags()?
+            } else {
+                ServerTags::new()
+            },
+        })
+    }
+
+    fn has_tag(&self, name: &str, wanted: &[String]) -> bool {
+        self.tags
+            .get(name)
+            .is_some_and(|tags| wanted.iter().any(|t| tags.contains(t)))
+    }
+
+    pub fn selects(&self, name: &str) -> bool {
+        let included = (self.include.is_empty() && self.include_tags.is_empty())
+            || self.include.iter().any(|p| p.matches(name))
+            || self.has_tag(name, &self.include_tags);
+        included
+            && !self.exclude.iter().any(|p| p.matches(name))
+            && !self.has_tag(name, &self.exclude_tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> ServerFilter {
+        let strings = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+        ServerFilter::new(Some(SyncFilter {
+            include: strings(include),
+            exclude: strings(exclude),
+            ..Default::default()
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn include_and_exclude_match_names_and_globs() {
+        assert!(filter(&[], &[]).selects("anything"));
+
+        let f = filter(&["internal-*", "docs"], &["internal-old"]);
+        assert!(f.selects("internal-search"));
+        assert!(f.selects("docs"));
+        assert!(!f.selects("internal-old"));
+        assert!(!f.selects("github"));
+
+        let f = filter(&[], &["*-dev"]);
+        assert!(f.selects("github"));
+        assert!(!f.selects("github-dev"));
+
+        let invalid = SyncFilter {
+            include: vec!["[".to_string()],
+            ..Default::default()
+        };
+        assert!(ServerFilter::new(Some(invalid)).is_err());
+    }
+
+    #[test]
+    fn tags_add_to_names_and_exclusion_wins() {
+        let tags = |t: &[&str]| t.iter().map(|s| s.to_string()).collect::<BTreeSet<_>>();
+        let f = ServerFilter {
+            include: vec![Pattern::new("docs").unwrap()],
+            include_tags: vec!["work".to_string()],
+            exclude_tags: vec!["broken".to_string()],
+            tags: ServerTags::from([
+                ("github".to_string(), tags(&["work"])),
+                ("jira".to_string(), tags(&["work", "broken"])),
+                ("docs".to_string(), tags(&["broken"])),
+            ]),
+            ..Default::default()
+        };
+        assert!(f.selects("github"));
+        assert!(!f.selects("jira"));
+        assert!(!f.selects("docs"));
+        assert!(!f.selects("untagged"));
+    }
+}
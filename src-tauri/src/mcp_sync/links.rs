@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use super::SyncMode;
+use super::fan_out::SyncTarget;
+use super::filter::{ServerFilter, SyncFilter};
+use super::strategy::ConflictStrategy;
+use super::watch;
+use crate::backend::get_backend;
+use crate::{atomic_write, config_lock};
+
+// Sync links: a source kept in sync with its targets by the watcher, stored
+// as a list in ~/.config/mcplinker/links.json. A link runs the same fan-out
+// sync as sync_mcp_config_to_targets every time its source changes.
+
+/// A source synced to its targets whenever it changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncLink {
+    pub id: String,
+    pub source: SyncTarget,
+    pub targets: Vec<SyncTarget>,
+    pub mode: SyncMode,
+    #[serde(default)]
+    pub conflict_strategy: Option<ConflictStrategy>,
+    #[serde(default)]
+    pub filter: Option<SyncFilter>,
+    /// A paused link is not watched
+    #[serde(default)]
+    pub paused: bool,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_run: Option<LinkRun>,
+}
+
+/// How the last sync of a link went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkRun {
+    pub at: DateTime<Utc>,
+    pub committed: bool,
+    pub error: Option<String>,
+}
+
+fn links_path() -> Result<PathBuf, String> {
+    let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    Ok(home.join(".config/mcplinker/links.json"))
+}
+
+pub fn load_links() -> Result<Vec<SyncLink>, String> {
+    let path = links_path()?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read sync links: {}", e)),
+    }
+}
+
+/// Read-modify-write the links under their lock
+async fn edit_links<T>(
+    edit: impl FnOnce(&mut Vec<SyncLink>) -> Result<T, String>,
+) -> Result<T, String> {
+    let path = links_path()?;
+    let _lock = config_lock::lock(&path).await?;
+    let mut links = load_links()?;
+    let result = edit(&mut links)?;
+    let content = serde_json::to_string_pretty(&links)
+        .map_err(|e| format!("Failed to serialize sync links: {}", e))?;
+    atomic_write::write_file(&path, content.into_bytes()).await?;
+    Ok(result)
+}
+
+fn find<'a>(links: &'a mut [SyncLink], id: &str) -> Result<&'a mut SyncLink, String> {
+    links
+        .iter_mut()
+        .find(|l| l.id == id)
+        .ok_or_else(|| format!("Sync link '{}' not found", id))
+}
+
+/// Store the outcome of a link's last sync
+pub(crate) async fn record_run(id: &str, run: LinkRun) -> Result<(), String> {
+    edit_links(|links| {
+        find(links, id)?.last_run = Some(run);
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_sync_links() -> Result<Vec<SyncLink>, String> {
+    load_links()
+}
+
+/// Link a source to its targets and sync it once. `mode` defaults to
+/// `three_way`, so edits made in a target are kept.
+#[tauri::command]
+pub async fn create_sync_link(
+    source: SyncTarget,
+    targets: Vec<SyncTarget>,
+    mode: Option<SyncMode>,
+    conflict_strategy: Option<ConflictStrategy>,
+    filter: Option<SyncFilter>,
+) -> Result<SyncLink, String> {
+    if targets.is_empty() {
+        return Err("A sync link needs at least one target".to_string());
+    }
+    if targets.contains(&source) {
+        return Err(format!(
+            "Client '{}' can't be linked to itself",
+            source.client_name
+        ));
+    }
+    for end in std::iter::once(&source).chain(&targets) {
+        get_backend(&end.client_name, end.path.as_deref())?;
+    }
+    ServerFilter::new(filter.clone())?;
+
+    let now = Utc::now();
+    let link = SyncLink {
+        id: format!("{}-{}", now.format("%Y%m%dT%H%M%S%3fZ"), source.client_name),
+        source,
+        targets,
+        mode: mode.unwrap_or(SyncMode::ThreeWay),
+        conflict_strategy,
+        filter,
+        paused: false,
+        created_at: now,
+        last_run: None,
+    };
+    let created = link.clone();
+    edit_links(|links| {
+        links.push(link);
+        Ok(())
+    })
+    .await?;
+    println!(
+        "[Watch] Linked {} -> {:?}",
+        created.source.client_name,
+        created
+            .targets
+            .iter()
+            .map(|t| &t.client_name)
+            .collect::<Vec<_>>()
+    );
+
+    watch::reload()?;
+    watch::trigger(&created.id);
+    Ok(created)
+}
+
+/// Stop or resume watching a link; a resumed link syncs right away
+#[tauri::command]
+pub async fn set_sync_link_paused(id: String, paused: bool) -> Result<SyncLink, String> {
+    let link = edit_links(|links| {
+        let link = find(links, &id)?;
+        link.paused = paused;
+        Ok(link.clone())
+    })
+    .await?;
+
+    watch::reload()?;
+    if !paused {
+        watch::trigger(&id);
+    }
+    Ok(link)
+}
+
+#[tauri::command]
+pub async fn remove_sync_link(id: String) -> Result<Vec<SyncLink>, String> {
+    let links = edit_links(|links| {
+        find(links, &id)?;
+        links.retain(|l| l.id != id);
+        Ok(links.clone())
+    })
+    .await?;
+
+    watch::reload()?;
+    Ok(links)
+}
@@ -0,0 +1,218 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use super::base::SyncBase;
+use crate::backend::ServerSet;
+
+/// A server's config and whether it is disabled
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerState {
+    pub config: Value,
+    pub disabled: bool,
+}
+
+fn state(set: &ServerSet, name: &str) -> Option<ServerState> {
+    set.get(name).map(|(config, disabled)| ServerState {
+        config: config.clone(),
+        disabled,
+    })
+}
+
+/// A server that changed on both sides since the last sync, in different
+/// ways. `None` means the server is (or was) absent on that side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    pub name: String,
+    pub base: Option<ServerState>,
+    pub source: Option<ServerState>,
+    pub target: Option<ServerState>,
+}
+
+/// Merge `source` into `target` against `base`. A server changed only in the
+/// source is copied over, removals included; one changed only in the target
+/// stays as it is. Servers changed on both sides keep the target version and
+/// are returned as conflicts.
+pub fn three_way(
+    source: &ServerSet,
+    target: &ServerSet,
+    base: &SyncBase,
+) -> (ServerSet, Vec<SyncConflict>) {
+    let mut merged = target.clone();
+    let mut conflicts = Vec::new();
+
+    let names: BTreeSet<&String> = source
+        .names()
+        .chain(target.names())
+        .chain(base.source.names())
+        .chain(base.target.names())
+        .collect();
+    for name in names {
+        let from = state(source, name);
+        let to = state(target, name);
+        let source_changed = from != state(&base.source, name);
+        let target_changed = to != state(&base.target, name);
+
+        if !source_changed || from == to {
+            continue;
+        }
+        if !target_changed {
+            match from {
+                Some(s) => merged.insert(name.clone(), s.config, s.disabled),
+                None => {
+                    merged.remove(name);
+                }
+            }
+            continue;
+        }
+        conflicts.push(SyncConflict {
+            name: name.clone(),
+            base: state(&base.source, name),
+            source: from,
+            target: to,
+        });
+    }
+    (merged, conflicts)
+}
+
+/// The base to record after writing `target`. Servers that were not synced,
+/// such as conflicts, keep their previous base, so a conflict is reported
+/// again until one side is changed to match the other.
+pub fn next_base<'a>(
+    source: &ServerSet,
+    target: &ServerSet,
+    previous: &SyncBase,
+    unsynced: impl IntoIterator<Item = &'a String>,
+) -> SyncBase {
+    let mut base = SyncBase {
+        source: source.clone(),
+        target: target.clone(),
+        synced_at: Some(chrono::Utc::now()),
+    };
+    for name in unsynced {
+        for (next, prev) in [
+            (&mut base.source, &previous.source),
+            (&mut base.target, &previous.target),
+        ] {
+            match prev.get(name) {
+                Some((config, disabled)) => next.insert(name.clone(), config.clone(), disabled),
+                None => {
+                    next.remove(name);
+                }
+            }
+        }
+    }
+    base
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn set(active: Value, disabled: Value) -> ServerSet {
+        serde_json::from_value(json!({"mcpServers": active, "__disabled": disabled})).unwrap()
+    }
+
+    fn base(source: &ServerSet, target: &ServerSet) -> SyncBase {
+        SyncBase {
+            source: source.clone(),
+            target: target.clone(),
+            synced_at: None,
+        }
+    }
+
+    #[test]
+    fn copies_changes_made_only_in_the_source() {
+        let before = set(
+            json!({"a": {"command": "a"}, "b": {"command": "b"}}),
+            json!({}),
+        );
+        let source = set(
+            json!({"a": {"command": "a2"}, "c": {"command": "c"}}),
+            json!({}),
+        );
+        let (merged, conflicts) = three_way(&source, &before, &base(&before, &before));
+
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, source);
+    }
+
+    #[test]
+    fn keeps_changes_made_only_in_the_target() {
+        let before = set(json!({"a": {"command": "a"}}), json!({}));
+        let target = set(json!({}), json!({"a": {"command": "a"}}));
+        let (merged, conflicts) = three_way(&before, &target, &base(&before, &before));
+
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, target);
+    }
+
+    #[test]
+    fn reports_servers_changed_on_both_sides() {
+        let before = set(
+            json!({"a": {"command": "a"}, "b": {"command": "b"}}),
+            json!({}),
+        );
+        let source = set(
+            json!({"a": {"command": "from-source"}, "b": {"command": "same"}}),
+            json!({}),
+        );
+        let target = set(
+            json!({"a": {"command": "from-target"}, "b": {"command": "same"}}),
+            json!({}),
+        );
+        let (merged, conflicts) = three_way(&source, &target, &base(&before, &before));
+
+        assert_eq!(merged, target);
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.name, "a");
+        assert_eq!(conflict.base.as_ref().unwrap().config["command"], "a");
+        assert_eq!(
+            conflict.source.as_ref().unwrap().config["command"],
+            "from-source"
+        );
+        assert_eq!(
+            conflict.target.as_ref().unwrap().config["command"],
+            "from-target"
+        );
+    }
+
+    #[test]
+    fn a_removal_against_a_change_is_a_conflict() {
+        let before = set(json!({"a": {"command": "a"}}), json!({}));
+        let source = set(json!({}), json!({}));
+        let target = set(json!({"a": {"command": "a2"}}), json!({}));
+        let (merged, conflicts) = three_way(&source, &target, &base(&before, &before));
+
+        assert_eq!(merged, target);
+        assert_eq!(conflicts[0].source, None);
+    }
+
+    #[test]
+    fn unsynced_servers_keep_their_previous_base() {
+        let previous = base(
+            &set(json!({"a": {"command": "a"}}), json!({})),
+            &set(json!({"a": {"command": "a"}}), json!({})),
+        );
+        let source = set(
+            json!({"a": {"command": "from-source"}, "b": {"command": "b"}}),
+            json!({}),
+        );
+        let target = set(
+            json!({"a": {"command": "from-target"}, "b": {"command": "b"}}),
+            json!({}),
+        );
+        let unsynced = ["a".to_string(), "gone".to_string()];
+        let next = next_base(&source, &target, &previous, &unsynced);
+
+        assert_eq!(next.source.active["a"]["command"], "a");
+        assert_eq!(next.target.active["a"]["command"], "a");
+        assert_eq!(next.source.active["b"], source.active["b"]);
+        assert!(!next.source.contains("gone"));
+        assert!(next.synced_at.is_some());
+    }
+}
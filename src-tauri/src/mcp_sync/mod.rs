@@ -0,0 +1,369 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::backend::codec;
+use crate::backend::preview::{self, ServerChange};
+use crate::backend::{ClientBackend, ServerSet, get_backend};
+use crate::server_meta::{self, ServerMeta};
+
+pub mod base;
+pub mod fan_out;
+pub mod filter;
+pub mod links;
+pub mod merge;
+pub mod report;
+pub mod strategy;
+pub mod tags;
+pub mod watch;
+
+use base::{Endpoint, SyncBase, load_base, save_base};
+use filter::{ServerFilter, SyncFilter};
+use merge::SyncConflict;
+use report::{ReportInput, ServerReport, SkipReason, SyncAction, SyncReport, build_report};
+use strategy::ConflictStrategy;
+
+/// How the source servers are combined with the target's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    /// The target becomes a copy of the source
+    Override,
+    /// Servers missing from the target are added, nothing else changes
+    AddMissing,
+    /// Changes since the last sync of this pair are carried over; servers
+    /// changed on both sides are reported as conflicts
+    ThreeWay,
+}
+
+impl SyncMode {
+    fn resolve(mode: Option<SyncMode>, override_all: bool) -> SyncMode {
+        mode.unwrap_or(if override_all {
+            SyncMode::Override
+        } else {
+            SyncMode::AddMissing
+        })
+    }
+}
+
+/// A sync to one target, computed but not written yet
+pub(crate) struct PlannedSync {
+    pub target_end: Endpoint,
+    pub target: Box<dyn ClientBackend>,
+    /// The target's servers before the sync, used to roll back
+    pub current: ServerSet,
+    pub planned: ServerSet,
+    /// `planned` as the target will store it
+    pub after: ServerSet,
+    pub base: SyncBase,
+    pub conflicts: Vec<SyncConflict>,
+    /// Servers left out by the filter; the target keeps them as they are
+    pub excluded: BTreeSet<String>,
+    /// Recorded metadata of the source servers, carried to the target
+    pub source_meta: BTreeMap<String, ServerMeta>,
+    pub report: SyncReport,
+}
+
+/// Add servers that don't exist in either active or disabled servers of the target
+fn add_missing(from: &ServerSet, to: &ServerSet) -> ServerSet {
+    let mut to = to.clone();
+    for (k, v) in &from.active {
+        if !to.contains(k) {
+            to.active.insert(k.clone(), v.clone());
+        }
+    }
+    for (k, v) in &from.disabled {
+        if !to.contains(k) {
+            to.disabled.insert(k.clone(), v.clone());
+        }
+    }
+    to
+}
+
+/// Read a target and work out what syncing `from` into it would do
+pub(crate) async fn plan_sync(
+    source_end: &Endpoint,
+    from: &ServerSet,
+    target_end: Endpoint,
+    mode: SyncMode,
+    filter: &ServerFilter,
+    strategy: Option<ConflictStrategy>,
+) -> Result<PlannedSync, String> {
+    let target = get_backend(&target_end.client, target_end.path.as_deref())?;
+    // A missing config reads as empty; any other failure stops this target
+    // before anything is written
+    let current = target
+        .read_servers()
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", target.location(), e))?;
+    let base = load_base(source_end, &target_end)?;
+    let source_location = get_backend(&source_end.client, source_end.path.as_deref())?.location();
+    let source_meta = server_meta::load(&source_end.client, &source_location);
+    let target_meta = server_meta::load(&target_end.client, &target.location());
+
+    let (mut planned, mut conflicts) = match mode {
+        SyncMode::Override => (from.clone(), Vec::new()),
+        SyncMode::AddMissing => (add_missing(from, &current), Vec::new()),
+        SyncMode::ThreeWay => merge::three_way(from, &current, &base),
+    };
+
+    // Servers the filter leaves out stay as they are in the target, active or disabled
+    let excluded: BTreeSet<String> = from
+        .names()
+        .chain(current.names())
+        .filter(|name| !filter.selects(name))
+        .cloned()
+        .collect();
+    for name in &excluded {
+        planned.remove(name);
+        if let Some((config, disabled)) = current.get(name) {
+            planned.insert(name.clone(), config.clone(), disabled);
+        }
+    }
+    conflicts.retain(|c| !excluded.contains(&c.name));
+
+    // Servers both sides have in different versions are settled by the
+    // strategy; without one, a three-way sync leaves them as conflicts
+    let resolution = match strategy.or(ConflictStrategy::default_for(mode)) {
+        Some(strategy) => {
+            let stored = strategy::as_stored(
+                from,
+                &source_end.client,
+                &target_end.client,
+                target.as_ref(),
+            );
+            let names = strategy::collisions(mode, &stored, &current, &conflicts, &excluded);
+            let resolution = strategy::resolve(
+                strategy,
+                &names,
+                (from, &stored),
+                &current,
+                &mut planned,
+                &source_end.client,
+                (&source_meta, &target_meta),
+            )?;
+            conflicts.retain(|c| !resolution.strategies.contains_key(&c.name));
+            resolution
+        }
+        None => Default::default(),
+    };
+    if !conflicts.is_empty() {
+        println!(
+            "[Sync] {} -> {}: conflicts on {:?}",
+            source_end.client,
+            target_end.client,
+            conflicts.iter().map(|c| &c.name).collect::<Vec<_>>()
+        );
+    }
+
+    // A server the target can't run as it is gets bridged to a transport it
+    // can, then rewritten in the target's field names; one it can't take at
+    // all keeps its current state instead of failing the whole sync
+    let capabilities = target.capabilities();
+    let mut decided = BTreeMap::new();
+    let mut dropped = BTreeMap::new();
+    let requested = planned.clone();
+    let incoming: Vec<(String, serde_json::Value, bool)> = planned
+        .names()
+        .filter_map(|name| {
+            let (config, disabled) = planned.get(name)?;
+            (current.get(name) != Some((config, disabled)))
+                .then(|| (name.clone(), config.clone(), disabled))
+        })
+        .collect();
+    for (name, config, disabled) in incoming {
+        let outcome = match capabilities.adapt_transport(&target_end.client, config.clone()) {
+            Err(e) => Some(ServerReport::skipped(
+                &name,
+                SkipReason::UnsupportedTransport,
+                Some(e),
+            )),
+            Ok(adapted) => {
+                let translation = codec::translate(&source_end.client, &target_end.client, adapted);
+                if translation.config != config {
+                    planned.insert(name.clone(), translation.config.clone(), disabled);
+                }
+                if !translation.dropped.is_empty() {
+                    dropped.insert(name.clone(), translation.dropped);
+                }
+                target
+                    .normalize_server(&name, translation.config)
+                    .err()
+                    .map(|e| ServerReport::failed(&name, e))
+            }
+        };
+        if let Some(outcome) = outcome {
+            println!("[Sync] {} -> {}: {:?}", name, target_end.client, outcome);
+            planned.remove(&name);
+            if let Some((config, disabled)) = current.get(&name) {
+                planned.insert(name.clone(), config.clone(), disabled);
+            }
+            decided.insert(name, outcome);
+        }
+    }
+
+    let change = ServerChange::Replace {
+        servers: planned.clone(),
+    };
+    let after = preview::apply(target.as_ref(), &target_end.client, &current, change)?;
+    let diff = preview::diff_server_sets(&current, &after);
+    let report = build_report(
+        ReportInput {
+            mode,
+            from,
+            current: &current,
+            requested: &requested,
+            after: &after,
+            conflicts: &conflicts,
+            decided: &decided,
+            dropped: &dropped,
+            excluded: &excluded,
+            resolution: &resolution,
+        },
+        diff,
+    );
+    Ok(PlannedSync {
+        target_end,
+        target,
+        current,
+        planned,
+        after,
+        base,
+        conflicts,
+        excluded,
+        source_meta,
+        report,
+    })
+}
+
+/// Write a planned sync to its target; returns whether anything was written
+pub(crate) async fn write_sync(plan: &PlannedSync) -> Result<bool, String> {
+    if plan.report.diff.is_empty() {
+        return Ok(false);
+    }
+    plan.target.write_servers(&plan.planned).await?;
+    Ok(true)
+}
+
+/// Record the merge base once the sync is final. Conflicts and servers left
+/// out by the filter keep their previous base, so a later sync still sees
+/// their changes. Servers written to the target take the source's metadata.
+pub(crate) async fn commit_sync(
+    source_end: &Endpoint,
+    from: &ServerSet,
+    plan: &PlannedSync,
+) -> Result<(), String> {
+    let unsynced = plan.conflicts.iter().map(|c| &c.name).chain(&plan.excluded);
+    let next = merge::next_base(from, &plan.after, &plan.base, unsynced);
+    save_base(source_end, &plan.target_end, &next)?;
+
+    let mut entries = BTreeMap::new();
+    for server in &plan.report.servers {
+        let source_name = server.renamed_from.as_ref().unwrap_or(&server.name);
+        let meta = match server.action {
+            SyncAction::Added | SyncAction::Updated => Some(
+                plan.source_meta
+                    .get(source_name)
+                    .cloned()
+                    .unwrap_or_else(ServerMeta::now),
+            ),
+            SyncAction::Removed => None,
+            _ => continue,
+        };
+        entries.insert(server.name.clone(), meta);
+    }
+    let location = plan.target.location();
+    if let Err(e) = server_meta::set(&plan.target_end.client, &location, entries).await {
+        println!(
+            "[Meta] Failed to record synced servers in {}: {}",
+            location, e
+        );
+    }
+    Ok(())
+}
+
+/// Copy servers from one client to another and report, per server, what
+/// happened in the target. `mode` defaults to `override` or `add_missing` following
+/// `override_all`. Servers both sides have in different versions are settled
+/// by `conflict_strategy`, which defaults to what the mode does. With `filter`
+/// only the selected servers are synced. With `dry_run` nothing is written.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn sync_mcp_config(
+    from_client: String,
+    to_client: String,
+    from_path: Option<String>,
+    to_path: Option<String>,
+    override_all: bool,
+    mode: Option<SyncMode>,
+    conflict_strategy: Option<ConflictStrategy>,
+    filter: Option<SyncFilter>,
+    dry_run: Option<bool>,
+) -> Result<SyncReport, String> {
+    let mode = SyncMode::resolve(mode, override_all);
+    let filter = ServerFilter::new(filter)?;
+    let source_end = Endpoint::new(&from_client, from_path.as_deref());
+    let source = get_backend(&from_client, from_path.as_deref())?;
+
+    // Load source and target in the normalized shape; each backend handles
+    // its own disabled representation (__disabled, "disabled": true, enabled = false)
+    let from = source.read_servers().await?;
+    let target_end = Endpoint::new(&to_client, to_path.as_deref());
+    let plan = plan_sync(
+        &source_end,
+        &from,
+        target_end,
+        mode,
+        &filter,
+        conflict_strategy,
+    )
+    .await?;
+
+    if !dry_run.unwrap_or(false) {
+        write_sync(&plan).await?;
+        commit_sync(&source_end, &from, &plan).await?;
+    }
+    Ok(plan.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn plan_sync_stops_on_an_unreadable_target() {
+        crate::test_home::init();
+        let dir = tempfile::tempdir().unwrap();
+        let (source, target) = (dir.path().join("source"), dir.path().join("target"));
+        let source_end = Endpoint::new("cursor", source.to_str());
+        let target_end = Endpoint::new("cursor", target.to_str());
+        let from: ServerSet =
+            serde_json::from_value(json!({"mcpServers": {"a": {"command": "a"}}})).unwrap();
+        let plan = |end: Endpoint| {
+            let (source_end, from) = (source_end.clone(), from.clone());
+            async move {
+                plan_sync(
+                    &source_end,
+                    &from,
+                    end,
+                    SyncMode::Override,
+                    &ServerFilter::default(),
+                    None,
+                )
+                .await
+            }
+        };
+
+        // A target that doesn't exist yet is empty
+        let planned = plan(target_end.clone()).await.unwrap();
+        assert_eq!(planned.current, ServerSet::default());
+
+        let config = target.join(".cursor/mcp.json");
+        std::fs::create_dir_all(config.parent().unwrap()).unwrap();
+        std::fs::write(&config, "{ not json").unwrap();
+        let Err(err) = plan(target_end).await else {
+            panic!("an unreadable target was planned");
+        };
+        assert!(err.contains("Failed to read"), "{}", err);
+    }
+}
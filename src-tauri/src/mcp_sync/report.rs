@@ -0,0 +1,206 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::SyncMode;
+use super::merge::SyncConflict;
+use super::strategy::{ConflictStrategy, Resolution};
+use crate::backend::ServerSet;
+use crate::backend::preview::{FieldChange, ServerSetDiff, diff_fields};
+
+/// What a sync did with one server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncAction {
+    Added,
+    Updated,
+    Removed,
+    Unchanged,
+    Skipped,
+    /// Changed on both sides since the last sync; left as it is in the target
+    Conflict,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The target already has a server with this name
+    AlreadyPresent,
+    /// Only the target changed the server since the last sync
+    TargetChanged,
+    /// The server is disabled and the target can't keep disabled servers
+    Disabled,
+    /// The target can't run the server's transport
+    UnsupportedTransport,
+}
+
+/// One server in a sync report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerReport {
+    pub name: String,
+    pub action: SyncAction,
+    /// Whether the server is disabled in the target afterwards
+    pub disabled: bool,
+    pub reason: Option<SkipReason>,
+    pub error: Option<String>,
+    /// Fields the target rewrote to store the server in its own format
+    pub converted: Vec<FieldChange>,
+    /// Fields of the source entry the target has no place for
+    pub dropped: Vec<String>,
+    /// The strategy that settled a server both sides had in different versions
+    pub strategy: Option<ConflictStrategy>,
+    /// The source name of a server added under a new name
+    pub renamed_from: Option<String>,
+}
+
+impl ServerReport {
+    fn new(name: &str, action: SyncAction) -> Self {
+        Self {
+            name: name.to_string(),
+            action,
+            disabled: false,
+            reason: None,
+            error: None,
+            converted: Vec::new(),
+            dropped: Vec::new(),
+            strategy: None,
+            renamed_from: None,
+        }
+    }
+
+    pub fn skipped(name: &str, reason: SkipReason, error: Option<String>) -> Self {
+        Self {
+            reason: Some(reason),
+            error,
+            ..Self::new(name, SyncAction::Skipped)
+        }
+    }
+
+    pub fn failed(name: &str, error: String) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::new(name, SyncAction::Failed)
+        }
+    }
+}
+
+/// What a sync changed, or would change, in one target
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    /// No server failed and none is in conflict
+    pub complete: bool,
+    pub servers: Vec<ServerReport>,
+    pub diff: ServerSetDiff,
+    pub conflicts: Vec<SyncConflict>,
+    /// Servers the filter left out
+    pub excluded: Vec<String>,
+}
+
+/// Everything a sync decided, in one place
+pub struct ReportInput<'a> {
+    pub mode: SyncMode,
+    pub from: &'a ServerSet,
+    pub current: &'a ServerSet,
+    /// What the sync asked the target to store, before it was adapted or converted
+    pub requested: &'a ServerSet,
+    pub after: &'a ServerSet,
+    pub conflicts: &'a [SyncConflict],
+    /// Servers already decided while planning: skipped or failed
+    pub decided: &'a BTreeMap<String, ServerReport>,
+    /// Fields dropped when translating each server for the target
+    pub dropped: &'a BTreeMap<String, Vec<String>>,
+    pub excluded: &'a BTreeSet<String>,
+    pub resolution: &'a Resolution,
+}
+
+/// One entry per selected server found in the source or the target, sorted by name
+pub fn build_report(input: ReportInput, diff: ServerSetDiff) -> SyncReport {
+    let conflicts: BTreeSet<&String> = input.conflicts.iter().map(|c| &c.name).collect();
+    let names: BTreeSet<&String> = input
+        .from
+        .names()
+        .chain(input.current.names())
+        .chain(input.after.names())
+        .collect();
+
+    let mut servers = Vec::new();
+    for name in names {
+        if input.excluded.contains(name) {
+            continue;
+        }
+        let after = input.after.get(name);
+        let disabled = after.is_some_and(|(_, disabled)| disabled);
+        let strategy = input.resolution.strategies.get(name).copied();
+        let renamed_from = input.resolution.renamed.get(name);
+        if let Some(decided) = input.decided.get(name) {
+            servers.push(ServerReport {
+                disabled,
+                strategy,
+                renamed_from: renamed_from.cloned(),
+                ..decided.clone()
+            });
+            continue;
+        }
+        if conflicts.contains(name) {
+            servers.push(ServerReport {
+                disabled,
+                ..ServerReport::new(name, SyncAction::Conflict)
+            });
+            continue;
+        }
+
+        let before = input.current.get(name);
+        let source = input.from.get(renamed_from.unwrap_or(name));
+        let mut report = match (before, after) {
+            (None, Some(_)) => ServerReport::new(name, SyncAction::Added),
+            (Some(_), None) => ServerReport::new(name, SyncAction::Removed),
+            (Some(b), Some(a)) if b != a => ServerReport::new(name, SyncAction::Updated),
+            (None, None) => match source {
+                // Only the source has it, and the target dropped it on write
+                Some((_, true)) => ServerReport::skipped(name, SkipReason::Disabled, None),
+                _ => ServerReport::new(name, SyncAction::Unchanged),
+            },
+            // Asked to change, but the target stores it the same way as before
+            (Some(b), Some(_)) if input.requested.get(name) != Some(b) => {
+                ServerReport::new(name, SyncAction::Unchanged)
+            }
+            (Some(_), Some(a)) => match source {
+                Some(s) if s != a => {
+                    let reason = match input.mode {
+                        SyncMode::ThreeWay => SkipReason::TargetChanged,
+                        _ => SkipReason::AlreadyPresent,
+                    };
+                    ServerReport::skipped(name, reason, None)
+                }
+                _ => ServerReport::new(name, SyncAction::Unchanged),
+            },
+        };
+        report.disabled = disabled;
+        report.strategy = strategy;
+        report.renamed_from = renamed_from.cloned();
+        if matches!(report.action, SyncAction::Added | SyncAction::Updated) {
+            report.dropped = input.dropped.get(name).cloned().unwrap_or_default();
+        }
+
+        // A copied server that doesn't come out the same was converted
+        if let (SyncAction::Added | SyncAction::Updated, Some((from_config, _)), Some((to, _))) =
+            (report.action, source, after)
+        {
+            diff_fields("", Some(from_config), Some(to), &mut report.converted);
+        }
+        servers.push(report);
+    }
+
+    let complete = !servers
+        .iter()
+        .any(|s| matches!(s.action, SyncAction::Failed | SyncAction::Conflict));
+    SyncReport {
+        complete,
+        servers,
+        diff,
+        conflicts: input.conflicts.to_vec(),
+        excluded: input.excluded.iter().cloned().collect(),
+    }
+}
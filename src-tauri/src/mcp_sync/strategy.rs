@@ -0,0 +1,409 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::SyncMode;
+use super::merge::SyncConflict;
+use crate::backend::{ClientBackend, ServerSet, codec};
+use crate::server_meta::ServerMeta;
+
+/// How a server that both sides have, in different versions, is settled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    PreferSource,
+    PreferTarget,
+    /// The version mcp-linker wrote last; the target's when that can't be told
+    Newer,
+    /// Keep the target's version and add the source's as `<name>-<source client>`
+    Rename,
+    /// Stop the sync
+    Fail,
+}
+
+impl ConflictStrategy {
+    /// What a mode does with collisions when no strategy is given. Three-way
+    /// syncs leave them in place and report them as conflicts.
+    pub fn default_for(mode: SyncMode) -> Option<Self> {
+        match mode {
+            SyncMode::Override => Some(Self::PreferSource),
+            SyncMode::AddMissing => Some(Self::PreferTarget),
+            SyncMode::ThreeWay => None,
+        }
+    }
+}
+
+/// Which strategy settled each colliding server
+#[derive(Debug, Default)]
+pub struct Resolution {
+    pub strategies: BTreeMap<String, ConflictStrategy>,
+    /// Name given to a renamed source server, to its original name
+    pub renamed: BTreeMap<String, String>,
+}
+
+/// The source's servers as the target would store them, bridged, translated
+/// and normalized like a sync writes them, so the same server reads as equal
+/// on both sides. An entry the target can't take is compared as it is.
+pub fn as_stored(
+    from: &ServerSet,
+    source_client: &str,
+    target_client: &str,
+    target: &dyn ClientBackend,
+) -> ServerSet {
+    let capabilities = target.capabilities();
+    let mut stored = ServerSet::default();
+    for name in from.names() {
+        let Some((config, disabled)) = from.get(name) else {
+            continue;
+        };
+        let config = capabilities
+            .adapt_transport(target_client, config.clone())
+            .unwrap_or_else(|_| config.clone());
+        let config = codec::translate(source_client, target_client, config).config;
+        let config = target
+            .normalize_server(name, config.clone())
+            .unwrap_or(config);
+        stored.insert(name.clone(), config, disabled);
+    }
+    stored
+}
+
+/// Selected servers both sides have in different versions, with `from` as
+/// [`as_stored`] gives it. For a three-way sync only the servers changed on
+/// both sides since the last sync collide.
+pub fn collisions(
+    mode: SyncMode,
+    from: &ServerSet,
+    current: &ServerSet,
+    conflicts: &[SyncConflict],
+    excluded: &BTreeSet<String>,
+) -> Vec<String> {
+    if mode == SyncMode::ThreeWay {
+        return conflicts.iter().map(|c| c.name.clone()).collect();
+    }
+    from.names()
+        .filter(|name| !excluded.contains(*name))
+        .filter(|name| matches!(current.get(name), Some(to) if from.get(name) != Some(to)))
+        .cloned()
+        .collect()
+}
+
+fn updated_at(meta: &BTreeMap<String, ServerMeta>, name: &str) -> Option<DateTime<Utc>> {
+    meta.get(name)?.updated_at
+}
+
+fn set_state(set: &mut ServerSet, name: &str, state: Option<(&Value, bool)>) {
+    match state {
+        Some((config, disabled)) => set.insert(name.to_string(), config.clone(), disabled),
+        None => {
+            set.remove(name);
+        }
+    }
+}
+
+/// A free name for the source's copy of `name`, with `incoming` in the shape
+/// the target stores it. A copy left by an earlier sync is reused, so
+/// repeated syncs don't pile up copies.
+fn rename_target(
+    name: &str,
+    source_client: &str,
+    incoming: Option<(&Value, bool)>,
+    from: &ServerSet,
+    planned: &ServerSet,
+) -> String {
+    let stem = format!("{}-{}", name, source_client);
+    let mut candidate = stem.clone();
+    let mut n = 2;
+    loop {
+        match planned.get(&candidate) {
+            Some(existing) if Some(existing) == incoming => return candidate,
+            None if !from.contains(&candidate) => return candidate,
+            _ => {}
+        }
+        candidate = format!("{}-{}", stem, n);
+        n += 1;
+    }
+}
+
+/// Settle every colliding server in `planned` with `strategy`. `from` holds
+/// the source's servers as read and as [`as_stored`] gives them, `meta` the
+/// recorded metadata of the source and the target servers.
+pub fn resolve(
+    strategy: ConflictStrategy,
+    names: &[String],
+    (from, stored): (&ServerSet, &ServerSet),
+    current: &ServerSet,
+    planned: &mut ServerSet,
+    source_client: &str,
+    meta: (&BTreeMap<String, ServerMeta>, &BTreeMap<String, ServerMeta>),
+) -> Result<Resolution, String> {
+    if strategy == ConflictStrategy::Fail && !names.is_empty() {
+        return Err(format!(
+            "Servers differ between source and target: {}",
+            names.join(", ")
+        ));
+    }
+
+    let mut resolution = Resolution::default();
+    for name in names {
+        let source = from.get(name);
+        let target = current.get(name);
+        match strategy {
+            ConflictStrategy::PreferSource => set_state(planned, name, source),
+            ConflictStrategy::PreferTarget | ConflictStrategy::Fail => {
+                set_state(planned, name, target)
+            }
+            ConflictStrategy::Newer => {
+                let source_time = source.and_then(|_| updated_at(meta.0, name));
+                let target_time = target.and_then(|_| updated_at(meta.1, name));
+                match (source_time, target_time) {
+                    (Some(s), Some(t)) if s > t => set_state(planned, name, source),
+                    (Some(_), None) => set_state(planned, name, source),
+                    _ => set_state(planned, name, target),
+                }
+            }
+            ConflictStrategy::Rename => {
+                set_state(planned, name, target);
+                // A server the source removed has nothing to rename
+                if let Some((config, disabled)) = source {
+                    let renamed =
+                        rename_target(name, source_client, stored.get(name), from, planned);
+                    planned.insert(renamed.clone(), config.clone(), disabled);
+                    resolution.strategies.insert(renamed.clone(), strategy);
+                    resolution.renamed.insert(renamed, name.clone());
+                }
+            }
+        }
+        resolution.strategies.insert(name.clone(), strategy);
+    }
+    Ok(resolution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::claude_code::ClaudeCodeBackend;
+    use crate::server_meta::CREATOR;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn set(active: Value) -> ServerSet {
+        serde_json::from_value(json!({ "mcpServers": active })).unwrap()
+    }
+
+    fn meta(name: &str, secs: i64) -> BTreeMap<String, ServerMeta> {
+        BTreeMap::from([(
+            name.to_string(),
+            ServerMeta {
+                creator: CREATOR.to_string(),
+                updated_at: Utc.timestamp_opt(secs, 0).single(),
+            },
+        )])
+    }
+
+    fn run(
+        strategy: ConflictStrategy,
+        from: &ServerSet,
+        current: &ServerSet,
+        meta: (&BTreeMap<String, ServerMeta>, &BTreeMap<String, ServerMeta>),
+    ) -> Result<(ServerSet, Resolution), String> {
+        let mut planned = current.clone();
+        let names = vec!["a".to_string()];
+        let resolution = resolve(
+            strategy,
+            &names,
+            (from, from),
+            current,
+            &mut planned,
+            "cursor",
+            meta,
+        )?;
+        Ok((planned, resolution))
+    }
+
+    #[test]
+    fn prefer_source_and_target_pick_a_side() {
+        let from = set(json!({"a": {"command": "source"}}));
+        let current = set(json!({"a": {"command": "target"}}));
+        let none = BTreeMap::new();
+
+        let (planned, _) = run(
+            ConflictStrategy::PreferSource,
+            &from,
+            &current,
+            (&none, &none),
+        )
+        .unwrap();
+        assert_eq!(planned.active["a"]["command"], "source");
+        let (planned, resolution) = run(
+            ConflictStrategy::PreferTarget,
+            &from,
+            &current,
+            (&none, &none),
+        )
+        .unwrap();
+        assert_eq!(planned.active["a"]["command"], "target");
+        assert_eq!(resolution.strategies["a"], ConflictStrategy::PreferTarget);
+    }
+
+    #[test]
+    fn fail_stops_on_any_collision() {
+        let from = set(json!({"a": {"command": "source"}}));
+        let current = set(json!({"a": {"command": "target"}}));
+        let none = BTreeMap::new();
+
+        let err = run(ConflictStrategy::Fail, &from, &current, (&none, &none)).unwrap_err();
+        assert!(err.contains('a'));
+    }
+
+    #[test]
+    fn newer_takes_the_later_write_and_the_target_when_unknown() {
+        let from = set(json!({"a": {"command": "source"}}));
+        let current = set(json!({"a": {"command": "target"}}));
+        let (old, new, none) = (meta("a", 100), meta("a", 200), BTreeMap::new());
+
+        let (planned, _) = run(ConflictStrategy::Newer, &from, &current, (&new, &old)).unwrap();
+        assert_eq!(planned.active["a"]["command"], "source");
+        let (planned, _) = run(ConflictStrategy::Newer, &from, &current, (&old, &new)).unwrap();
+        assert_eq!(planned.active["a"]["command"], "target");
+        let (planned, _) = run(ConflictStrategy::Newer, &from, &current, (&new, &none)).unwrap();
+        assert_eq!(planned.active["a"]["command"], "source");
+        let (planned, _) = run(ConflictStrategy::Newer, &from, &current, (&none, &none)).unwrap();
+        assert_eq!(planned.active["a"]["command"], "target");
+    }
+
+    #[test]
+    fn rename_keeps_the_target_and_adds_the_source_copy() {
+        let from = set(json!({"a": {"command": "source"}}));
+        let current = set(json!({"a": {"command": "target"}}));
+        let none = BTreeMap::new();
+
+        let (planned, resolution) =
+            run(ConflictStrategy::Rename, &from, &current, (&none, &none)).unwrap();
+        assert_eq!(planned.active["a"]["command"], "target");
+        assert_eq!(planned.active["a-cursor"]["command"], "source");
+        assert_eq!(resolution.renamed["a-cursor"], "a");
+    }
+
+    #[test]
+    fn rename_target_reuses_a_copy_and_skips_taken_names() {
+        let incoming = json!({"command": "source"});
+        let from = set(json!({"a": incoming.clone(), "a-cursor": {"command": "x"}}));
+
+        // The source has its own a-cursor, so the copy goes one further
+        let planned = set(json!({}));
+        assert_eq!(
+            rename_target("a", "cursor", Some((&incoming, false)), &from, &planned),
+            "a-cursor-2"
+        );
+
+        // A copy from an earlier sync is reused
+        let planned = set(json!({"a-cursor-2": incoming.clone()}));
+        assert_eq!(
+            rename_target("a", "cursor", Some((&incoming, false)), &from, &planned),
+            "a-cursor-2"
+        );
+
+        // A different server under that name is left alone
+        let planned = set(json!({"a-cursor-2": {"command": "other"}}));
+        assert_eq!(
+            rename_target("a", "cursor", Some((&incoming, false)), &from, &planned),
+            "a-cursor-3"
+        );
+    }
+
+    // A Cursor entry and the same server as Claude Code stores it
+    fn cursor_source() -> ServerSet {
+        set(json!({"a": {"command": "npx", "args": ["-y", "srv"], "env": {"K": "v"}}}))
+    }
+
+    fn claude_code_target() -> ServerSet {
+        set(
+            json!({"a": {"type": "stdio", "command": "npx", "args": ["-y", "srv"], "env": {"K": "v"}}}),
+        )
+    }
+
+    #[test]
+    fn fail_ignores_the_same_server_in_another_clients_shape() {
+        let target = ClaudeCodeBackend::new("/project");
+        let from = cursor_source();
+        let current = claude_code_target();
+        let stored = as_stored(&from, "cursor", "claude_code", &target);
+        let none = BTreeMap::new();
+
+        let names = collisions(SyncMode::Override, &stored, &current, &[], &BTreeSet::new());
+        assert!(names.is_empty());
+        let mut planned = from.clone();
+        resolve(
+            ConflictStrategy::Fail,
+            &names,
+            (&from, &stored),
+            &current,
+            &mut planned,
+            "cursor",
+            (&none, &none),
+        )
+        .unwrap();
+
+        // A real difference still stops the sync
+        let mut changed = from.clone();
+        changed.active["a"]["args"] = json!(["-y", "other"]);
+        let stored = as_stored(&changed, "cursor", "claude_code", &target);
+        let names = collisions(SyncMode::Override, &stored, &current, &[], &BTreeSet::new());
+        assert_eq!(names, ["a"]);
+        let err = resolve(
+            ConflictStrategy::Fail,
+            &names,
+            (&changed, &stored),
+            &current,
+            &mut planned,
+            "cursor",
+            (&none, &none),
+        );
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn rename_twice_reuses_the_copy_across_clients() {
+        let target = ClaudeCodeBackend::new("/project");
+        let mut from = cursor_source();
+        from.active["a"]["args"] = json!(["-y", "newer"]);
+        let stored = as_stored(&from, "cursor", "claude_code", &target);
+        let none = BTreeMap::new();
+
+        let mut current = claude_code_target();
+        for _ in 0..2 {
+            let names = collisions(
+                SyncMode::AddMissing,
+                &stored,
+                &current,
+                &[],
+                &BTreeSet::new(),
+            );
+            assert_eq!(names, ["a"]);
+            // The copy from the first sync is already in the target
+            let mut planned = current.clone();
+            resolve(
+                ConflictStrategy::Rename,
+                &names,
+                (&from, &stored),
+                &current,
+                &mut planned,
+                "cursor",
+                (&none, &none),
+            )
+            .unwrap();
+            // Written to the target, the copy takes Claude Code's shape
+            current = as_stored(&planned, "cursor", "claude_code", &target);
+            current.insert(
+                "a".to_string(),
+                claude_code_target().active["a"].clone(),
+                false,
+            );
+        }
+        let names: Vec<&String> = current.names().collect();
+        assert_eq!(names, ["a", "a-cursor"]);
+        assert_eq!(current.active["a-cursor"], stored.active["a"]);
+    }
+}
@@ -0,0 +1,81 @@
+use dirs::home_dir;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use crate::atomic_write;
+use crate::config_lock;
+
+// Tags the user gives to servers, by server name, in ~/.config/mcplinker/tags.json.
+// Tags follow the name, so a server is tagged the same way in every client.
+
+/// Server name to its tags
+pub type ServerTags = BTreeMap<String, BTreeSet<String>>;
+
+fn tags_path() -> Result<PathBuf, String> {
+    let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    Ok(home.join(".config/mcplinker/tags.json"))
+}
+
+pub fn load_tags() -> Result<ServerTags, String> {
+    let path = tags_path()?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(ServerTags::new()),
+        Err(e) => Err(format!("Failed to read server tags: {}", e)),
+    }
+}
+
+#[tauri::command]
+pub async fn list_server_tags() -> Result<ServerTags, String> {
+    load_tags()
+}
+
+/// Replace the tags of one server; an empty list removes them
+#[tauri::command]
+pub async fn set_server_tags(server_name: String, tags: Vec<String>) -> Result<ServerTags, String> {
+    let path = tags_path()?;
+    let _lock = config_lock::lock(&path).await?;
+
+    let mut all = load_tags()?;
+    let tags: BTreeSet<String> = tags
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if tags.is_empty() {
+        all.remove(&server_name);
+    } else {
+        all.insert(server_name, tags);
+    }
+
+    let content = serde_json::to_string_pretty(&all)
+        .map_err(|e| format!("Failed to serialize server tags: {}", e))?;
+    atomic_write::write_file(&path, content.into_bytes()).await?;
+    Ok(all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn set_server_tags_trims_and_clears() {
+        crate::test_home::init();
+        let name = "tags-test-server".to_string();
+
+        let all = set_server_tags(name.clone(), vec![" work ".into(), "".into(), "ci".into()])
+            .await
+            .unwrap();
+        assert_eq!(all[&name], BTreeSet::from(["ci".into(), "work".into()]));
+        assert_eq!(load_tags().unwrap()[&name], all[&name]);
+
+        let all = set_server_tags(name.clone(), vec![" ".into()])
+            .await
+            .unwrap();
+        assert!(!all.contains_key(&name));
+        assert!(!load_tags().unwrap().contains_key(&name));
+    }
+}
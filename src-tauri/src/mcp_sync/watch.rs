@@ -0,0 +1,291 @@
+use chrono::Utc;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use super::fan_out::{SyncTarget, TargetStatus, sync_mcp_config_to_targets};
+use super::links::{LinkRun, SyncLink, load_links, record_run};
+use crate::backend::get_backend;
+use crate::config_lock::fnv1a;
+
+// The watcher behind sync links. The config files of every active link's
+// source are watched through their directories, since configs are replaced
+// rather than edited in place. A change runs the link once its files have
+// been quiet for DEBOUNCE.
+//
+// Loop prevention: the content of every watched file is remembered as it was
+// last seen, and after a run the targets it wrote are remembered as written.
+// An event for a file whose content is what was last seen is ignored, so a
+// target that is also the source of another link doesn't bounce the change
+// back. Chains are not followed either: link the source to every client.
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+enum Trigger {
+    /// A file in a watched directory changed
+    Changed(PathBuf),
+    /// Run a link now
+    Run(String),
+}
+
+#[derive(Default)]
+struct WatchState {
+    sender: Option<UnboundedSender<Trigger>>,
+    /// Dropping the watcher stops it
+    watcher: Option<RecommendedWatcher>,
+    /// Watched file to the links it is the source of
+    routes: HashMap<PathBuf, Vec<String>>,
+    /// Content hash of a watched or written file when last seen; `None` when missing
+    seen: HashMap<PathBuf, Option<u64>>,
+}
+
+static STATE: Lazy<Mutex<WatchState>> = Lazy::new(|| Mutex::new(WatchState::default()));
+
+fn state() -> std::sync::MutexGuard<'static, WatchState> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A file as the watcher reports it: its resolved directory and its name
+fn watch_key(path: &Path) -> Option<PathBuf> {
+    let dir = path.parent()?.canonicalize().ok()?;
+    Some(dir.join(path.file_name()?))
+}
+
+fn content_hash(path: &Path) -> Option<u64> {
+    fs::read(path).ok().map(|bytes| fnv1a(&bytes))
+}
+
+fn config_files(end: &SyncTarget) -> Vec<PathBuf> {
+    match get_backend(&end.client_name, end.path.as_deref()) {
+        Ok(backend) => backend.config_files(),
+        Err(e) => {
+            println!("[Watch] {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Start watching the links and sync each active one once, to catch up with
+/// changes made while mcp-linker was closed
+pub async fn start() -> Result<(), String> {
+    let (sender, receiver) = unbounded_channel();
+    state().sender = Some(sender);
+    tokio::spawn(run_loop(receiver));
+
+    reload()?;
+    for link in load_links()?.iter().filter(|l| !l.paused) {
+        trigger(&link.id);
+    }
+    Ok(())
+}
+
+/// Watch the sources of the links as they are now stored
+pub fn reload() -> Result<(), String> {
+    let links = load_links()?;
+    let mut state = state();
+    let Some(sender) = state.sender.clone() else {
+        // Not started, nothing to watch with
+        return Ok(());
+    };
+
+    let mut routes: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    for link in links.iter().filter(|l| !l.paused) {
+        for file in config_files(&link.source) {
+            if let Some(key) = watch_key(&file) {
+                routes.entry(key).or_default().push(link.id.clone());
+            } else {
+                println!("[Watch] Can't watch {}: no such directory", file.display());
+            }
+        }
+    }
+
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            for path in event.paths {
+                let _ = sender.send(Trigger::Changed(path));
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+    let dirs: BTreeSet<&Path> = routes.keys().filter_map(|key| key.parent()).collect();
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    }
+
+    for key in routes.keys() {
+        if !state.seen.contains_key(key) {
+            let hash = content_hash(key);
+            state.seen.insert(key.clone(), hash);
+        }
+    }
+    println!(
+        "[Watch] Watching {} file(s) for {} link(s)",
+        routes.len(),
+        links.iter().filter(|l| !l.paused).count()
+    );
+    state.routes = routes;
+    state.watcher = Some(watcher);
+    Ok(())
+}
+
+/// Run a link as soon as the watcher is idle
+pub fn trigger(id: &str) {
+    if let Some(sender) = &state().sender {
+        let _ = sender.send(Trigger::Run(id.to_string()));
+    }
+}
+
+async fn run_loop(mut receiver: UnboundedReceiver<Trigger>) {
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        // Wait for the burst of events a single save makes to settle
+        loop {
+            match tokio::time::timeout(DEBOUNCE, receiver.recv()).await {
+                Ok(Some(trigger)) => batch.push(trigger),
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+        for id in due_links(batch) {
+            run_link(&id).await;
+        }
+    }
+}
+
+/// Links to run for a batch of triggers; changes that leave a file as it was
+/// last seen, including the watcher's own writes, are dropped
+fn due_links(batch: Vec<Trigger>) -> BTreeSet<String> {
+    let mut state = state();
+    let mut due = BTreeSet::new();
+    for trigger in batch {
+        match trigger {
+            Trigger::Run(id) => {
+                due.insert(id);
+            }
+            Trigger::Changed(path) => {
+                let Some(ids) = state.routes.get(&path).cloned() else {
+                    continue;
+                };
+                let hash = content_hash(&path);
+                if state.seen.get(&path) != Some(&hash) {
+                    state.seen.insert(path, hash);
+                    due.extend(ids);
+                }
+            }
+        }
+    }
+    due
+}
+
+async fn run_link(id: &str) {
+    let link: Option<SyncLink> = match load_links() {
+        Ok(links) => links.into_iter().find(|l| l.id == id && !l.paused),
+        Err(e) => {
+            println!("[Watch] {}", e);
+            None
+        }
+    };
+    let Some(link) = link else {
+        return;
+    };
+    println!(
+        "[Watch] Syncing link {} from {}",
+        id, link.source.client_name
+    );
+
+    let result = sync_mcp_config_to_targets(
+        link.source.client_name.clone(),
+        link.source.path.clone(),
+        link.targets.clone(),
+        false,
+        Some(link.mode),
+        link.conflict_strategy,
+        link.filter.clone(),
+        None,
+    )
+    .await;
+
+    let run = match result {
+        Ok(result) => {
+            // What this run wrote is not a change to pass on
+            let written = result.targets.iter().filter(|t| {
+                matches!(
+                    t.status,
+                    TargetStatus::Synced | TargetStatus::RolledBack | TargetStatus::RollbackFailed
+                )
+            });
+            let mut state = state();
+            for target in written {
+                let end = SyncTarget {
+                    client_name: target.client_name.clone(),
+                    path: target.path.clone(),
+                };
+                for key in config_files(&end).iter().filter_map(|f| watch_key(f)) {
+                    let hash = content_hash(&key);
+                    state.seen.insert(key, hash);
+                }
+            }
+            LinkRun {
+                at: Utc::now(),
+                committed: result.committed,
+                error: result.targets.into_iter().find_map(|t| t.error),
+            }
+        }
+        Err(e) => LinkRun {
+            at: Utc::now(),
+            committed: false,
+            error: Some(e),
+        },
+    };
+    if let Some(error) = &run.error {
+        println!("[Watch] Link {} failed: {}", id, error);
+    }
+    if let Err(e) = record_run(id, run).await {
+        println!("[Watch] Failed to record run of {}: {}", id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn due_links_drops_changes_already_seen() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("mcp.json");
+        fs::write(&file, "{}").unwrap();
+        let key = watch_key(&file).unwrap();
+        {
+            let mut state = state();
+            state.routes.insert(key.clone(), vec!["link".to_string()]);
+            state.seen.insert(key.clone(), content_hash(&key));
+        }
+        let changed = || vec![Trigger::Changed(key.clone())];
+
+        // An event that leaves the file as it was is noise
+        assert!(due_links(changed()).is_empty());
+
+        fs::write(&file, "{\"mcpServers\": {}}").unwrap();
+        assert_eq!(due_links(changed()), BTreeSet::from(["link".to_string()]));
+        assert!(due_links(changed()).is_empty());
+
+        // A run records what it wrote, so its own write doesn't trigger again
+        fs::write(&file, "{\"mcpServers\": {\"a\": {}}}").unwrap();
+        state().seen.insert(key.clone(), content_hash(&key));
+        assert!(due_links(changed()).is_empty());
+
+        // Files no link reads from are ignored; explicit runs always go through
+        let other = Trigger::Changed(dir.path().join("other.json"));
+        let due = due_links(vec![other, Trigger::Run("manual".to_string())]);
+        assert_eq!(due, BTreeSet::from(["manual".to_string()]));
+
+        state().routes.remove(&key);
+    }
+}
@@ -0,0 +1,298 @@
+use chrono::{DateTime, Utc};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use crate::backend::get_backend;
+use crate::backend::registry::builtin_clients;
+use crate::client_descriptor::all_descriptors;
+use crate::{atomic_write, config_lock};
+
+// What mcp-linker knows about the servers it added or changed, kept out of the
+// client configs in ~/.config/mcplinker/server_meta.json:
+//
+//   { "migrated": { "<client>": ... }, "servers": { "<client>": { "<location>": { "<server>": ServerMeta } } } }
+//
+// The location is the config file, or the project for Claude Code, as
+// reported by ClientBackend::location. Older versions wrote the same data
+// into every entry as `_creator` and `updated_at`; the migration moves it here.
+
+pub(crate) const CREATOR: &str = "mcp_linker";
+/// Keys older versions injected into server entries
+pub const INJECTED_KEYS: [&str; 2] = ["_creator", "updated_at"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerMeta {
+    pub creator: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ServerMeta {
+    /// Written by mcp-linker just now
+    pub fn now() -> Self {
+        Self {
+            creator: CREATOR.to_string(),
+            updated_at: Some(Utc::now()),
+        }
+    }
+}
+
+type Servers = BTreeMap<String, BTreeMap<String, BTreeMap<String, ServerMeta>>>;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MetaStore {
+    /// When each client's default config was migrated
+    #[serde(default)]
+    migrated: BTreeMap<String, DateTime<Utc>>,
+    #[serde(default)]
+    servers: Servers,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    let home = home_dir().ok_or_else(|| "Failed to get home directory".to_string())?;
+    Ok(home.join(".config/mcplinker/server_meta.json"))
+}
+
+fn load_store() -> Result<MetaStore, String> {
+    let path = store_path()?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(MetaStore::default()),
+        Err(e) => Err(format!("Failed to read server metadata: {}", e)),
+    }
+}
+
+/// Read-modify-write the store under its lock
+async fn edit_store<T>(edit: impl FnOnce(&mut MetaStore) -> T) -> Result<T, String> {
+    let path = store_path()?;
+    let _lock = config_lock::lock(&path).await?;
+    let mut store = load_store()?;
+    let result = edit(&mut store);
+    let content = serde_json::to_string_pretty(&store)
+        .map_err(|e| format!("Failed to serialize server metadata: {}", e))?;
+    atomic_write::write_file(&path, content.into_bytes()).await?;
+    Ok(result)
+}
+
+/// Metadata of every server recorded at one location
+pub fn load(client: &str, location: &str) -> BTreeMap<String, ServerMeta> {
+    match load_store() {
+        Ok(mut store) => store
+            .servers
+            .remove(client)
+            .and_then(|mut locations| locations.remove(location))
+            .unwrap_or_default(),
+        Err(e) => {
+            println!("[Meta] {}", e);
+            BTreeMap::new()
+        }
+    }
+}
+
+/// Record that mcp-linker just wrote these servers
+pub async fn touch(client: &str, location: &str, names: &[String]) -> Result<(), String> {
+    let entries = names
+        .iter()
+        .map(|n| (n.clone(), Some(ServerMeta::now())))
+        .collect();
+    set(client, location, entries).await
+}
+
+/// Set or clear the metadata of servers at one location
+pub async fn set(
+    client: &str,
+    location: &str,
+    entries: BTreeMap<String, Option<ServerMeta>>,
+) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    edit_store(|store| {
+        let locations = store.servers.entry(client.to_string()).or_default();
+        let servers = locations.entry(location.to_string()).or_default();
+        for (name, meta) in entries {
+            match meta {
+                Some(meta) => servers.insert(name, meta),
+                None => servers.remove(&name),
+            };
+        }
+        if servers.is_empty() {
+            locations.remove(location);
+        }
+        if locations.is_empty() {
+            store.servers.remove(client);
+        }
+    })
+    .await
+}
+
+/// Drop the metadata of removed servers
+pub async fn forget(client: &str, location: &str, names: &[String]) -> Result<(), String> {
+    let entries = names.iter().map(|n| (n.clone(), None)).collect();
+    set(client, location, entries).await
+}
+
+/// Keep the store in step with a change that went through; a failure here is
+/// logged rather than failing the change
+pub async fn record(client: &str, location: &str, names: &[String], removed: bool) {
+    let result = if removed {
+        forget(client, location, names).await
+    } else {
+        touch(client, location, names).await
+    };
+    if let Err(e) = result {
+        println!("[Meta] Failed to record {:?} in {}: {}", names, location, e);
+    }
+}
+
+/// Remove the keys older versions injected into an entry, returning what they said
+pub fn strip_injected(config: &mut Value) -> Option<ServerMeta> {
+    let obj = config.as_object_mut()?;
+    let [creator, updated_at] = INJECTED_KEYS.map(|key| obj.remove(key));
+    if creator.is_none() && updated_at.is_none() {
+        return None;
+    }
+    Some(ServerMeta {
+        creator: creator
+            .as_ref()
+            .and_then(|v| v.as_str())
+            .unwrap_or(CREATOR)
+            .to_string(),
+        updated_at: updated_at
+            .as_ref()
+            .and_then(|v| v.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc)),
+    })
+}
+
+/// Outcome of the migration for one client config
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataMigration {
+    pub client_name: String,
+    pub location: Option<String>,
+    /// Servers whose entries were cleaned
+    pub migrated: Vec<String>,
+    pub error: Option<String>,
+}
+
+async fn migrate_client(client: &str, path: Option<&str>) -> Result<(String, Vec<String>), String> {
+    let backend = get_backend(client, path)?;
+    let location = backend.location();
+    let mut servers = backend.read_servers().await?;
+
+    let mut stripped = BTreeMap::new();
+    for (name, config) in servers.active.iter_mut().chain(servers.disabled.iter_mut()) {
+        if let Some(meta) = strip_injected(config) {
+            stripped.insert(name.clone(), meta);
+        }
+    }
+    if stripped.is_empty() {
+        return Ok((location, Vec::new()));
+    }
+    let migrated: Vec<String> = stripped.keys().cloned().collect();
+    backend.write_servers(&servers).await?;
+
+    // An entry already in the store is newer than the injected keys
+    let known = load(client, &location);
+    let entries = stripped
+        .into_iter()
+        .filter(|(name, _)| !known.contains_key(name))
+        .map(|(name, meta)| (name, Some(meta)))
+        .collect();
+    set(client, &location, entries).await?;
+    println!(
+        "[Meta] Moved metadata of {} server(s) out of {}",
+        migrated.len(),
+        location
+    );
+    Ok((location, migrated))
+}
+
+/// Clients whose default config may carry the injected keys
+fn default_clients() -> Vec<String> {
+    builtin_clients()
+        .into_iter()
+        .map(|c| c.to_string())
+        .chain(all_descriptors().into_iter().map(|d| d.id))
+        // Claude Code needs a project, and neither it nor Codex ever had the keys
+        .filter(|c| c != "claude_code" && c != "codex")
+        .collect()
+}
+
+/// Move `_creator` / `updated_at` out of client configs into the store. With
+/// no client, every client config at its default location is migrated.
+#[tauri::command]
+pub async fn migrate_server_metadata(
+    client_name: Option<String>,
+    path: Option<String>,
+) -> Result<Vec<MetadataMigration>, String> {
+    let targets: Vec<(String, Option<String>)> = match client_name {
+        Some(client) => vec![(client, path)],
+        None => default_clients().into_iter().map(|c| (c, None)).collect(),
+    };
+
+    let mut results = Vec::new();
+    for (client, path) in targets {
+        let result = match migrate_client(&client, path.as_deref()).await {
+            Ok((location, migrated)) => MetadataMigration {
+                client_name: client,
+                location: Some(location),
+                migrated,
+                error: None,
+            },
+            Err(e) => MetadataMigration {
+                client_name: client,
+                location: None,
+                migrated: Vec::new(),
+                error: Some(e),
+            },
+        };
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Run the migration over each default client config once. A client that
+/// fails is tried again on the next start.
+pub async fn migrate_once() -> Result<(), String> {
+    let done = load_store()?.migrated;
+    let mut finished = Vec::new();
+    for client in default_clients() {
+        if done.contains_key(&client) {
+            continue;
+        }
+        match migrate_client(&client, None).await {
+            Ok(_) => finished.push(client),
+            Err(e) => println!("[Meta] Skipped {}: {}", client, e),
+        }
+    }
+    if finished.is_empty() {
+        return Ok(());
+    }
+    let now = Utc::now();
+    edit_store(|store| {
+        for client in finished {
+            store.migrated.insert(client, now);
+        }
+    })
+    .await
+}
+
+/// Metadata of the servers in one client config
+#[tauri::command]
+pub async fn get_server_metadata(
+    client_name: String,
+    path: Option<String>,
+) -> Result<BTreeMap<String, ServerMeta>, String> {
+    let backend = get_backend(&client_name, path.as_deref())?;
+    Ok(load(&client_name, &backend.location()))
+}
@@ -0,0 +1,19 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+// Tests that reach mcp-linker's own state under ~/.config/mcplinker (backups,
+// locks, sync bases, server metadata) or Claude Code's configs call `init`
+// first, so they run in a scratch home instead of the user's.
+
+static HOME: OnceLock<tempfile::TempDir> = OnceLock::new();
+
+/// Point HOME at a temp dir for the rest of the test run
+pub fn init() -> &'static Path {
+    HOME.get_or_init(|| {
+        let dir = tempfile::tempdir().expect("Failed to create test home");
+        // SAFETY: set once, and before the test that asked for it reads it
+        unsafe { std::env::set_var("HOME", dir.path()) };
+        dir
+    })
+    .path()
+}
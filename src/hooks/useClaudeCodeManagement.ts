@@ -10,8 +10,16 @@ interface ClaudeCodeServer {
   command?: string;
   args?: string[];
   env?: Record<string, string>;
+  headers?: Record<string, string>;
+  scope?: ClaudeScope;
+  overridden_by?: ClaudeScope;
+  approval?: McpjsonApproval;
 }
 
+type ClaudeScope = "local" | "project" | "user";
+
+type McpjsonApproval = "approved" | "rejected" | "pending";
+
 interface AddServerRequest {
   name: string;
   type: string;
@@ -1,7 +1,7 @@
 import { mustHavePathClients } from "@/lib/data";
 import { useClientPathStore } from "@/stores/clientPathStore";
 import { useCCProjectStore } from "@/stores/ccProject";
-import { ConfigType } from "@/types/mcpConfig";
+import { ConfigType, ServerOutcome } from "@/types/mcpConfig";
 import { invoke } from "@tauri-apps/api/core";
 import { useCallback, useEffect, useState } from "react";
 import { toast } from "sonner";
@@ -44,7 +44,20 @@ export function useMcpConfig(
         );
 
         const result = await Promise.race([operationPromise, timeoutPromise]);
-        if (showSuccessToast) toast.success(successMessage);
+        // Codex reports servers it left alone instead of failing
+        const skipped: ServerOutcome[] = (result?.outcomes ?? []).filter(
+          (o: ServerOutcome) => o.outcome !== "changed",
+        );
+        if (skipped.length > 0) {
+          const missing = skipped.filter((o) => o.outcome === "not_found");
+          const unchanged = skipped.filter((o) => o.outcome !== "not_found");
+          if (missing.length > 0)
+            toast.warning(`Not found: ${missing.map((o) => o.name).join(", ")}`);
+          if (unchanged.length > 0)
+            toast.info(
+              `Already in that state: ${unchanged.map((o) => o.name).join(", ")}`,
+            );
+        } else if (showSuccessToast) toast.success(successMessage);
         return result; // Return the result of the operation
       } catch (error) {
         const errorMessage =
@@ -84,6 +97,8 @@ export function useMcpConfig(
         );
         const mapped: any = { mcpServers: {} };
         for (const s of list || []) {
+          // Only the server Claude Code uses for each name
+          if (s.overridden_by) continue;
           if (s.type === "stdio") {
             mapped.mcpServers[s.name] = {
               type: "stdio",
@@ -5,3 +5,9 @@ export type ConfigType = {
     [key: string]: ServerConfig;
   };
 };
+
+/** What an operation did to one server, as reported by Codex */
+export type ServerOutcome = {
+  name: string;
+  outcome: "changed" | "already_in_state" | "not_found";
+};